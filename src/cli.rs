@@ -0,0 +1,119 @@
+use crate::config::{
+    default_user_defined, is_std, Config, EnumStyle, Lang, Naming, Spec,
+    Target, Types, CONFIG_FILE,
+};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+/// turn openapi.json into typescript functions and types
+///
+/// without any arguments every target in `argon.toml` is generated,
+/// when there is no config `argon-data/openapi.json` is used.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// path to the openapi spec, use `-` to read from stdin
+    /// [default: argon-data/openapi.json]
+    pub input: Option<PathBuf>,
+
+    /// output directory, use `-` to write to stdout [default: argon-data]
+    #[arg(short, long)]
+    pub out: Option<PathBuf>,
+
+    /// target language, can be repeated [default: ts]
+    #[arg(short, long = "target", value_enum)]
+    pub targets: Vec<Lang>,
+
+    /// degrade unsupported schemas into `unknown` with a warning
+    #[arg(long)]
+    pub lenient: bool,
+
+    /// do not write anything, fail if the generated files are out of date
+    #[arg(long)]
+    pub check: bool,
+
+    /// path to the config file [default: argon.toml]
+    #[arg(short, long, conflicts_with_all = ["input", "out", "targets"])]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// regenerate whenever the spec or the config changes
+    Watch {
+        /// how often to look for changes, in milliseconds
+        #[arg(long, default_value_t = 500)]
+        interval: u64,
+    },
+}
+
+impl Cli {
+    /// the config file in use, if any
+    pub fn config_path(&self) -> std::io::Result<Option<PathBuf>> {
+        if let Some(path) = &self.config {
+            return Ok(Some(path.clone()));
+        }
+
+        let flags = self.input.is_some()
+            || self.out.is_some()
+            || !self.targets.is_empty();
+        if !flags && std::fs::exists(CONFIG_FILE)? {
+            return Ok(Some(CONFIG_FILE.into()));
+        }
+
+        Ok(None)
+    }
+
+    /// the config described by the flags or the config file
+    pub fn config(&self) -> std::io::Result<Config> {
+        let mut config = self.load()?;
+        for spec in config.specs.iter_mut() {
+            spec.lenient |= self.lenient;
+        }
+        Ok(config)
+    }
+
+    fn load(&self) -> std::io::Result<Config> {
+        if let Some(path) = self.config_path()? {
+            return Config::load(&path);
+        }
+
+        let input =
+            self.input.clone().unwrap_or("argon-data/openapi.json".into());
+        let out = self.out.clone().unwrap_or("argon-data".into());
+        let langs = if self.targets.is_empty() {
+            vec![Lang::Ts]
+        } else {
+            self.targets.clone()
+        };
+
+        if is_std(&out) && langs.len() > 1 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "only one target can be written to stdout",
+            ));
+        }
+
+        let targets = langs
+            .into_iter()
+            .map(|lang| Target {
+                lang,
+                output: if is_std(&out) {
+                    out.clone()
+                } else {
+                    out.join(lang.file_name())
+                },
+                user_defined: default_user_defined(),
+                naming: Naming::default(),
+                types: Types::default(),
+                formats: Vec::new(),
+                enums: EnumStyle::default(),
+            })
+            .collect();
+
+        Ok(Config { specs: vec![Spec { input, lenient: false, targets }] })
+    }
+}
@@ -0,0 +1,349 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE: &str = "argon.toml";
+
+/// project config, usually checked in as `argon.toml`
+/// ```toml
+/// [[spec]]
+/// input = "argon-data/openapi.json"
+///
+/// [[spec.target]]
+/// lang = "ts"
+/// output = "web/src/api/gen.ts"
+/// user_defined = "./user_defined"
+/// naming = { functions = "camel" }
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(rename = "spec")]
+    pub specs: Vec<Spec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Spec {
+    /// path to the openapi spec, `-` for stdin
+    pub input: PathBuf,
+    /// degrade unsupported schemas into `unknown` with a warning
+    #[serde(default)]
+    pub lenient: bool,
+    #[serde(rename = "target")]
+    pub targets: Vec<Target>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Target {
+    pub lang: Lang,
+    /// path of the generated file, `-` for stdout
+    pub output: PathBuf,
+    /// import path of the user defined module
+    #[serde(default = "default_user_defined")]
+    pub user_defined: String,
+    #[serde(default)]
+    pub naming: Naming,
+    /// typescript types of formatted strings and integers
+    #[serde(default)]
+    pub types: Types,
+    /// types of the target language for other formats
+    #[serde(default)]
+    pub formats: Vec<Format>,
+    /// typescript output of named enums
+    #[serde(default)]
+    pub enums: EnumStyle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    Ts,
+    Dart,
+}
+
+impl Lang {
+    /// default file name of the generated output
+    pub const fn file_name(&self) -> &'static str {
+        match self {
+            Self::Ts => "gen.ts",
+            Self::Dart => "gen.dart",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Naming {
+    /// case of the generated function names
+    #[serde(default)]
+    pub functions: Case,
+    /// prepended to every generated function name
+    #[serde(default)]
+    pub prefix: String,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Case {
+    #[default]
+    Snake,
+    Camel,
+}
+
+impl Naming {
+    pub fn function(&self, name: &str) -> String {
+        let name = format!("{}{name}", self.prefix);
+        match self.functions {
+            Case::Snake => name,
+            Case::Camel => {
+                let mut out = String::with_capacity(name.len());
+                let mut up = false;
+                for c in name.chars() {
+                    if c == '_' {
+                        up = !out.is_empty();
+                        continue;
+                    }
+                    if up {
+                        out.extend(c.to_uppercase());
+                        up = false;
+                    } else {
+                        out.push(c);
+                    }
+                }
+                out
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnumStyle {
+    /// a union of the values
+    #[default]
+    Union,
+    /// the union and a `const` object of the members
+    Const,
+    /// a typescript `enum`, enums of booleans are `const` objects
+    Enum,
+}
+
+/// ```toml
+/// types = { date_time = "date", int64 = "bigint", byte = "uint8array" }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Types {
+    /// `format: date-time`
+    #[serde(default)]
+    pub date_time: DateType,
+    /// `format: date`
+    #[serde(default)]
+    pub date: DateType,
+    /// `format: int64` and `uint64`
+    #[serde(default)]
+    pub int64: Int64Type,
+    /// `format: byte`, base64 encoded bytes
+    #[serde(default)]
+    pub byte: ByteType,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateType {
+    #[default]
+    String,
+    Date,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Int64Type {
+    #[default]
+    Number,
+    Bigint,
+    String,
+}
+
+impl Int64Type {
+    pub const fn is_bigint(&self) -> bool {
+        matches!(self, Self::Bigint)
+    }
+
+    /// whether int64 values are converted, `bigint` and `string` are both
+    /// sent as a bigint that the `JSON.stringify` replacer writes as a number
+    pub const fn is_converted(&self) -> bool {
+        !matches!(self, Self::Number)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ByteType {
+    #[default]
+    String,
+    Uint8array,
+}
+
+impl Types {
+    /// whether json values are converted to and from any of the types
+    pub fn revives(&self) -> bool {
+        self.date_time == DateType::Date
+            || self.date == DateType::Date
+            || self.int64.is_converted()
+            || self.byte == ByteType::Uint8array
+    }
+
+    /// type aliases used by the generated typescript
+    pub fn ts(&self) -> String {
+        let date = |d: DateType| match d {
+            DateType::String => "string",
+            DateType::Date => "Date",
+        };
+        let int64 = match self.int64 {
+            Int64Type::Number => "number",
+            Int64Type::Bigint => "bigint",
+            Int64Type::String => "string",
+        };
+        let byte = match self.byte {
+            ByteType::String => "string",
+            ByteType::Uint8array => "Uint8Array",
+        };
+
+        let mut out = String::with_capacity(256);
+        for (alias, ty) in [
+            ("_DateTime", date(self.date_time)),
+            ("_Date", date(self.date)),
+            ("_Int64", int64),
+            ("_Byte", byte),
+        ] {
+            out += &format!("// @ts-ignore\ntype {alias} = {ty}\n");
+        }
+        out
+    }
+}
+
+/// a type of the target language for a `format` used by the spec
+/// ```toml
+/// formats = [{ format = "money", name = "Money", import = "./money" }]
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Format {
+    /// the schema type the format is used with
+    #[serde(rename = "type", default)]
+    pub schema_type: FormatType,
+    pub format: String,
+    /// the type used in the generated code
+    pub name: String,
+    /// `import { Money } from './money'` in typescript and
+    /// `import './money.dart'` in dart
+    pub import: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FormatType {
+    #[default]
+    String,
+    Integer,
+    Number,
+}
+
+impl FormatType {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Integer => "integer",
+            Self::Number => "number",
+        }
+    }
+}
+
+impl Format {
+    /// name of the type alias in the generated code,
+    /// e.g. `_string_money`
+    pub fn alias(schema_type: FormatType, format: &str) -> String {
+        let format: String = format
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        format!("_{}_{format}", schema_type.as_str())
+    }
+}
+
+impl Spec {
+    /// aliases of the formats that any target maps to a type
+    pub fn formats(&self) -> HashSet<String> {
+        self.targets
+            .iter()
+            .flat_map(|t| t.formats.iter())
+            .map(|f| Format::alias(f.schema_type, &f.format))
+            .collect()
+    }
+}
+
+pub fn default_user_defined() -> String {
+    "./user_defined".to_string()
+}
+
+impl Config {
+    /// loads the config, relative paths are resolved from its directory
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        let mut config: Self = toml::from_str(&data).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{}: {e}", path.display()),
+            )
+        })?;
+
+        let base = path.parent().unwrap_or(Path::new(""));
+        for spec in config.specs.iter_mut() {
+            spec.input = resolve(base, &spec.input);
+            for target in spec.targets.iter_mut() {
+                target.output = resolve(base, &target.output);
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn resolve(base: &Path, path: &Path) -> PathBuf {
+    if is_std(path) {
+        return path.to_path_buf();
+    }
+    base.join(path)
+}
+
+/// `-` stands for stdin / stdout
+pub fn is_std(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn function_names() {
+        let naming = |toml: &str| toml::from_str::<Naming>(toml).unwrap();
+
+        let snake = naming("");
+        assert_eq!(snake.function("api_user_get"), "api_user_get");
+
+        let camel = naming("functions = 'camel'");
+        assert_eq!(camel.function("api_user_get"), "apiUserGet");
+        assert_eq!(camel.function("_api__user_"), "apiUser");
+        assert_eq!(camel.function("get_2fa_code"), "get2faCode");
+
+        let prefixed = naming("functions = 'camel'\nprefix = 'fetch_'");
+        assert_eq!(prefixed.function("user_get"), "fetchUserGet");
+        let prefixed = naming("prefix = 'fetch_'");
+        assert_eq!(prefixed.function("user_get"), "fetch_user_get");
+    }
+}
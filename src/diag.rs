@@ -0,0 +1,91 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warning => f.write_str("warning"),
+            Self::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// a problem found in the spec
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diag {
+    pub severity: Severity,
+    /// json pointer into the spec. e.g. `#/paths/~1user/get/responses/200`
+    pub pointer: String,
+    pub message: String,
+}
+
+/// diagnostics collected while reading a spec
+#[derive(Debug, Default)]
+pub struct Diags {
+    pub list: Vec<Diag>,
+}
+
+impl Diags {
+    pub fn push(
+        &mut self, severity: Severity, pointer: &str, message: impl Display,
+    ) {
+        let diag = Diag {
+            severity,
+            pointer: pointer.to_string(),
+            message: message.to_string(),
+        };
+        // a schema that is referenced more than once is read again
+        if !self.list.contains(&diag) {
+            self.list.push(diag);
+        }
+    }
+
+    pub fn error(&mut self, pointer: &str, message: impl Display) {
+        self.push(Severity::Error, pointer, message);
+    }
+
+    pub fn warn(&mut self, pointer: &str, message: impl Display) {
+        self.push(Severity::Warning, pointer, message);
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.list.iter().filter(|d| d.severity == severity).count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.list.iter().any(|d| d.severity == Severity::Error)
+    }
+
+    /// prints every diagnostic to stderr, `file` is the spec they belong to
+    pub fn report(&self, file: &str) {
+        for d in self.list.iter() {
+            // pointers into other files already start with their path
+            let file = if d.pointer.starts_with('#') { file } else { "" };
+            if d.pointer.is_empty() {
+                eprintln!("{}: {}\n", d.severity, d.message);
+                continue;
+            }
+            eprintln!(
+                "{}: {}\n  --> {file}{}\n",
+                d.severity, d.message, d.pointer
+            );
+        }
+
+        let errors = self.count(Severity::Error);
+        let warnings = self.count(Severity::Warning);
+        if errors + warnings != 0 {
+            eprintln!("{file}: {errors} error(s), {warnings} warning(s)");
+        }
+    }
+}
+
+/// appends `token` to the json `pointer`, escaping it as per RFC 6901
+pub fn ptr(pointer: &str, token: impl Display) -> String {
+    let token = token.to_string().replace('~', "~0").replace('/', "~1");
+    format!("{pointer}/{token}")
+}
@@ -1,17 +1,147 @@
-use std::fs::read_to_string;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
-use openapi::OpenApi;
+use clap::Parser;
+use cli::{Cli, Command};
+use config::{is_std, Config, Spec, Target};
+use diag::Diags;
+use openapi::{docs::Docs, OpenApi};
 
+mod cli;
+mod config;
+mod diag;
 mod models;
 mod openapi;
+mod watch;
 
-fn main() -> std::io::Result<()> {
-    let oas = read_to_string("argon-data/openapi.json")?;
-    let oa: OpenApi = serde_json::from_str(&oas)?;
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    if let Some(Command::Watch { interval }) = &cli.command {
+        return watch::watch(&cli, Duration::from_millis(*interval));
+    }
 
-    // openapi::generate(&oa)?;
-    let asp = models::ApiSchema::from_openapi(&oa);
-    asp.generate()?;
+    let config = match cli.config() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    Ok(())
+    if !generate(&config, cli.check, &mut Vec::new()) {
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// runs every spec in the config, returns false if any of them failed.
+/// files referenced by the specs are added to `sources`
+fn generate(config: &Config, check: bool, sources: &mut Vec<PathBuf>) -> bool {
+    let mut failed = false;
+    for spec in config.specs.iter() {
+        let mut diags = Diags::default();
+        match run(spec, check, &mut diags, sources) {
+            Ok(fresh) => failed |= !fresh,
+            Err(e) => diags.error("", e),
+        }
+        diags.report(&spec.input.to_string_lossy());
+        failed |= diags.has_errors();
+    }
+
+    !failed
+}
+
+/// returns false if `check` is set and any of the outputs is out of date
+fn run(
+    spec: &Spec, check: bool, diags: &mut Diags, sources: &mut Vec<PathBuf>,
+) -> std::io::Result<bool> {
+    let oas = if is_std(&spec.input) {
+        let mut oas = String::new();
+        std::io::stdin().read_to_string(&mut oas)?;
+        oas
+    } else {
+        std::fs::read_to_string(&spec.input).map_err(at(&spec.input))?
+    };
+    let doc =
+        openapi::parse_document(&spec.input, &oas).map_err(at(&spec.input))?;
+    let mut docs = Docs::new(&spec.input, doc);
+    // broken operations and schemas are left out, the rest is still
+    // read so every problem of the spec is reported at once
+    let oa = OpenApi::from_document(docs.root(), spec.lenient, diags);
+    let asp = models::ApiSchema::from_openapi(
+        &oa,
+        &mut docs,
+        spec.lenient,
+        spec.formats(),
+        diags,
+    );
+    sources.extend(docs.paths().skip(1).cloned());
+    if diags.has_errors() {
+        return Ok(true);
+    }
+
+    let mut fresh = true;
+    for target in spec.targets.iter() {
+        let out = asp.generate(target);
+        if check {
+            fresh &= check_output(target, &out).map_err(at(&target.output))?;
+        } else {
+            write_output(target, &out).map_err(at(&target.output))?;
+        }
+    }
+
+    Ok(fresh)
+}
+
+/// names `path` in an io error, it has no pointer into the spec
+fn at(path: &Path) -> impl FnOnce(std::io::Error) -> std::io::Error + '_ {
+    move |e| std::io::Error::new(e.kind(), format!("{}: {e}", path.display()))
+}
+
+/// writes the output, files that are already up to date are not touched
+fn write_output(target: &Target, out: &str) -> std::io::Result<()> {
+    if is_std(&target.output) {
+        return std::io::stdout().write_all(out.as_bytes());
+    }
+
+    if std::fs::read(&target.output).is_ok_and(|old| old == out.as_bytes()) {
+        return Ok(());
+    }
+
+    if let Some(dir) = target.output.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&target.output, out)
+}
+
+/// compares `out` with the file on disk and prints a diff if they differ
+fn check_output(target: &Target, out: &str) -> std::io::Result<bool> {
+    if is_std(&target.output) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "stdout can not be checked",
+        ));
+    }
+
+    let old = match std::fs::read_to_string(&target.output) {
+        Ok(old) => old,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    if old == out {
+        return Ok(true);
+    }
+
+    let path = target.output.to_string_lossy();
+    let diff = similar::TextDiff::from_lines(old.as_str(), out)
+        .unified_diff()
+        .header(&path, &format!("{path} (generated)"))
+        .to_string();
+    std::io::stdout().write_all(diff.as_bytes())?;
+    eprintln!("{path} is out of date");
+
+    Ok(false)
 }
@@ -3,11 +3,17 @@
 use indexmap::IndexMap;
 use indoc::formatdoc;
 use route::ApiRoute;
-use types::ApiType;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::fmt::Display;
+use types::{ApiKind, ApiPrim, ApiType, Revive};
 
+use crate::config::{EnumStyle, Format, FormatType, Lang, Target};
+use crate::diag::{ptr, Diags};
+use crate::openapi::common::RefOr;
+use crate::openapi::docs::Docs;
+use crate::openapi::format::SchemaFormat;
 use crate::openapi::OpenApi;
-use std::collections::HashSet;
-use std::io::Write;
+use std::collections::{HashMap, HashSet};
 
 mod route;
 mod types;
@@ -17,122 +23,438 @@ pub struct ApiSchema {
     types: IndexMap<String, ApiType>,
     route: IndexMap<String, ApiRoute>,
     api_version: String,
+    /// aliases of the mapped formats in use, see [`Ctx::format`]
+    formats: IndexMap<String, ApiPrim>,
+}
+
+/// state shared while turning an [`OpenApi`] into an [`ApiSchema`]
+pub struct Ctx<'a> {
+    pub types: IndexMap<String, ApiType>,
+    /// location of the schema behind every type name, see [`Docs::loc`]
+    pub names: HashMap<String, String>,
+    pub docs: &'a mut Docs,
+    /// the document refs are currently resolved from
+    pub base: usize,
+    pub diags: &'a mut Diags,
+    /// degrade unsupported schemas into `unknown` instead of failing
+    pub lenient: bool,
+    /// aliases of the formats some target maps, see [`Format::alias`]
+    pub mapped: HashSet<String>,
+    /// the mapped formats in use and the type they have otherwise
+    pub formats: IndexMap<String, ApiPrim>,
+}
+
+impl Ctx<'_> {
+    /// reports a schema that argon can not model. in lenient mode this is
+    /// only a warning and `fallback` is used in its place
+    pub fn unsupported(
+        &mut self, loc: &str, message: impl Display, fallback: ApiKind,
+    ) -> ApiKind {
+        if self.lenient {
+            self.diags.warn(loc, message);
+            return fallback;
+        }
+
+        self.diags.error(loc, message);
+        ApiKind::Unknown
+    }
+
+    /// the alias of a format that a target maps to its own type,
+    /// targets that do not map it use `base`
+    pub fn format(
+        &mut self, schema_type: FormatType, format: &Option<SchemaFormat>,
+        base: &ApiPrim,
+    ) -> Option<String> {
+        let alias = Format::alias(schema_type, &format.as_ref()?.name());
+        if !self.mapped.contains(&alias) {
+            return None;
+        }
+        self.formats.entry(alias.clone()).or_insert_with(|| base.clone());
+        Some(alias)
+    }
+
+    /// runs `f` with refs resolved from the document `base`
+    pub fn with_base<R>(
+        &mut self, base: usize, f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let old = std::mem::replace(&mut self.base, base);
+        let r = f(self);
+        self.base = old;
+        r
+    }
+
+    /// follows refs to components such as parameters and responses.
+    /// returns the item, the document it is in and its location
+    pub fn resolve<T: Clone + DeserializeOwned>(
+        &mut self, item: &RefOr<T>, loc: &str,
+    ) -> Option<(T, usize, String)> {
+        let mut r = match item {
+            RefOr::T(t) => {
+                return Some((t.clone(), self.base, loc.to_string()))
+            }
+            RefOr::Ref(r) => r.loc.clone(),
+        };
+        let mut doc = self.base;
+        let mut loc = loc.to_string();
+
+        // a ref can point to another ref
+        for _ in 0..32 {
+            let (d, pointer) = match self.docs.locate(doc, &r) {
+                Ok(v) => v,
+                Err(e) => {
+                    self.diags.error(&loc, format!("{r}: {e}"));
+                    return None;
+                }
+            };
+            let key = self.docs.loc(d, &pointer);
+            let Some(value) = self.docs.get(d, &pointer) else {
+                self.diags.error(&loc, format!("ref not found: {r}"));
+                return None;
+            };
+
+            match RefOr::<T>::deserialize(value) {
+                Ok(RefOr::T(t)) => return Some((t, d, key)),
+                Ok(RefOr::Ref(next)) => {
+                    (doc, loc, r) = (d, key, next.loc);
+                }
+                Err(e) => {
+                    self.diags.error(&key, e);
+                    return None;
+                }
+            }
+        }
+
+        self.diags.error(&loc, format!("too many nested refs: {r}"));
+        None
+    }
 }
 
 impl ApiSchema {
-    pub fn from_openapi(oa: &OpenApi) -> Self {
+    /// problems with the spec are reported into `diags`, the schema
+    /// should not be generated if there are any errors
+    pub fn from_openapi(
+        oa: &OpenApi, docs: &mut Docs, lenient: bool, mapped: HashSet<String>,
+        diags: &mut Diags,
+    ) -> Self {
         let len = oa.components.schemas.len() + oa.paths.len() * 2;
-        let mut aps = Self {
+        let mut ctx = Ctx {
             types: IndexMap::with_capacity(len),
-            route: IndexMap::with_capacity(oa.paths.len()),
-            api_version: oa.info.version.clone(),
+            names: HashMap::with_capacity(len),
+            docs,
+            base: 0,
+            diags,
+            lenient,
+            mapped,
+            formats: IndexMap::new(),
         };
+        let mut route = IndexMap::with_capacity(oa.paths.len());
+
+        for k in oa.components.schemas.keys() {
+            let pointer = ptr("/components/schemas", k);
+            let (doc, pointer) = ctx.docs.target(0, &pointer);
+            ctx.names.insert(k.to_string(), ctx.docs.loc(doc, &pointer));
+        }
 
         for (k, v) in oa.components.schemas.iter() {
+            // already parsed as a ref of another schema
+            if ctx.types.contains_key(k) {
+                continue;
+            }
+
+            let loc = ptr("#/components/schemas", k);
             let at = ApiType::parse_openapi(
                 Some(k.to_string()),
                 v,
-                HashSet::from_iter([k.to_string()]),
-                &mut aps.types,
-                &oa.components.schemas,
+                &loc,
+                HashSet::from_iter([loc.clone()]),
+                &mut ctx,
             );
-            if matches!(at.kind, types::ApiKind::Unknown) {
-                panic!("unknown: {k:?} : {v:#?}");
-            }
-            aps.types.insert(k.to_string(), at);
+            ctx.types.insert(k.to_string(), at);
         }
 
         for (url, pi) in oa.paths.iter() {
+            let loc = ptr("#/paths", url);
             macro_rules! dop {
                 ($($name:ident),*) => {
                     $(if let Some(op) = &pi.$name {
                         let ar = ApiRoute::parse_openapi(
                             url,
                             stringify!($name),
+                            pi,
                             op,
-                            &mut aps.types,
-                            &oa.components.schemas,
+                            &loc,
+                            &mut ctx,
                         );
-                        aps.route.insert(ar.name.to_string(), ar);
+                        route.insert(ar.name.to_string(), ar);
                     })*
                 };
             }
 
-            dop!(get, put, post, delete, patch);
+            dop!(get, put, post, delete, patch, head, options, trace);
+        }
+
+        Self {
+            types: ctx.types,
+            route,
+            api_version: oa.info.version.clone(),
+            formats: ctx.formats,
         }
+    }
 
-        aps
+    pub fn generate(&self, target: &Target) -> String {
+        match target.lang {
+            Lang::Ts => self.generate_ts(target),
+            Lang::Dart => self.generate_dart(target),
+        }
     }
 
-    pub fn generate(&self) -> std::io::Result<()> {
-        let mut ts = std::fs::OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open("argon-data/gen.ts")?;
-
-        // let mut dart = std::fs::OpenOptions::new()
-        //     .write(true)
-        //     .create(true)
-        //     .truncate(true)
-        //     .open("argon-data/gog/bin/gen.dart")?;
-
-        ts.write_all(
-            formatdoc! {"
-                // DO NOT EDIT THIS FILE.
-                // THIS IS A GENERATED FILE AND ANY EDIT WILL BE REPLACED
-                // Home: github.com/00-team/argon
-                import * as ud from './user_defined';
-                export const API_VERSION = '{}';
-                
-                // @ts-ignore
-                type _Map<V> = {{ [key: string]: V }}
-                
-            ", self.api_version}
-            .as_bytes(),
-        )?;
-
-        // dart.write_all(
-        //     formatdoc! {r#"
-        //         // DO NOT EDIT THIS FILE.
-        //         // THIS IS A GENERATED FILE AND ANY EDIT WILL BE REPLACED
-        //         // import * as ud from './user_defined';
-        //
-        //         import 'package:http/http.dart' as http;
-        //         import 'package:freezed_annotation/freezed_annotation.dart';
-        //
-        //         part 'gen.freezed.dart';
-        //         part 'gen.g.dart';
-        //
-        //         const API_VERSION = "{}";
-        //
-        //         typedef JsonObject = Map<String, dynamic>;
-        //
-        //
-        //     "#, self.api_version}
-        //     .as_bytes(),
-        // )?;
+    /// the mapped formats in use, the type they have without a mapping
+    /// and the mapping of `target`
+    fn format_aliases<'a>(
+        &'a self, target: &'a Target,
+    ) -> impl Iterator<Item = (&'a String, &'a ApiPrim, Option<&'a Format>)>
+    {
+        self.formats.iter().map(|(alias, base)| {
+            let format = target
+                .formats
+                .iter()
+                .find(|f| &Format::alias(f.schema_type, &f.format) == alias);
+            (alias, base, format)
+        })
+    }
+
+    /// the names every import path of the formats in use provides
+    fn format_imports<'a>(
+        &'a self, target: &'a Target,
+    ) -> IndexMap<&'a str, Vec<&'a str>> {
+        let mut imports = IndexMap::<&str, Vec<&str>>::new();
+        for (_, _, format) in self.format_aliases(target) {
+            let Some(f) = format else { continue };
+            let Some(path) = &f.import else { continue };
+            let names = imports.entry(path).or_default();
+            if !names.contains(&f.name.as_str()) {
+                names.push(&f.name);
+            }
+        }
+        imports
+    }
+
+    fn generate_ts(&self, target: &Target) -> String {
+        let mut imports = String::new();
+        for (path, names) in self.format_imports(target) {
+            imports +=
+                &format!("\nimport {{ {} }} from '{path}';", names.join(", "));
+        }
+        let mut types = target.types.ts();
+        for (alias, base, format) in self.format_aliases(target) {
+            let ty = format.map_or_else(|| base.ts(false), |f| f.name.clone());
+            types += &format!("// @ts-ignore\ntype {alias} = {ty}\n");
+        }
+
+        let mut ts = formatdoc! {"
+            // DO NOT EDIT THIS FILE.
+            // THIS IS A GENERATED FILE AND ANY EDIT WILL BE REPLACED
+            // Home: github.com/00-team/argon
+            import * as ud from '{}';{}
+            export const API_VERSION = '{}';
+            
+            // @ts-ignore
+            type _Map<V> = {{ [key: string]: V }}
+            {}
+            // @ts-ignore
+            function _param(name: string, value: unknown, style: string, explode: boolean, reserved = false, raw = false): string {{
+                if (value == null) return ''
+                let enc = (v: unknown) => raw ? String(v) : reserved ? encodeURI(String(v)) : encodeURIComponent(String(v))
+                let n = encodeURIComponent(name)
+                let obj = typeof value == 'object' && !Array.isArray(value)
+                let entries = obj ? Object.entries(value as object).filter(([, v]) => v != null) : []
+                let items = obj ? [] : (Array.isArray(value) ? value : [value]).map(enc)
+                let kv = (sep: string) => entries.map(([k, v]) => enc(k) + sep + enc(v))
+                // objects that are not exploded are a list of keys and values
+                let flat = obj ? kv(',') : items
+                switch (style) {{
+                    case 'simple':
+                        return (obj && explode ? kv('=') : flat).join(',')
+                    case 'label':
+                        return '.' + (obj && explode ? kv('=') : flat).join(explode ? '.' : ',')
+                    case 'matrix':
+                        if (obj && explode) return kv('=').map(x => ';' + x).join('')
+                        if (explode) return items.map(x => `;${{n}}=${{x}}`).join('')
+                        return `;${{n}}=` + flat.join(',')
+                    case 'form':
+                        if (obj && explode) return kv('=').join('&')
+                        if (explode) return items.map(x => `${{n}}=${{x}}`).join('&')
+                        return `${{n}}=` + flat.join(',')
+                    case 'spaceDelimited':
+                        return `${{n}}=` + flat.join('%20')
+                    case 'pipeDelimited':
+                        return `${{n}}=` + flat.join('|')
+                    case 'deepObject':
+                        return entries.map(([k, v]) => `${{n}}[${{enc(k)}}]=${{enc(v)}}`).join('&')
+                }}
+                return ''
+            }}
+            // bodies of undocumented responses can be anything, even empty
+            // @ts-ignore
+            async function _unknown(r: {{ text(): Promise<string> }}): Promise<unknown> {{
+                let text = await r.text()
+                try {{ return JSON.parse(text) }} catch {{ return text }}
+            }}
+
+        ", target.user_defined, imports, self.api_version, types};
 
         for (n, r) in self.types.iter() {
             // if r.user_defined {
             //     continue;
             // }
 
+            if target.enums == EnumStyle::Enum {
+                if let Some(def) = r.enum_def_ts() {
+                    ts += &def;
+                    continue;
+                }
+            }
+
             let body = r.body_ts(true, false);
-            let exp = format!("export type {n} = {body};\n");
-            ts.write_all(exp.as_bytes())?;
+            ts += &format!("export type {n} = {body};\n");
+            if target.enums != EnumStyle::Union {
+                ts.extend(r.enum_object_ts());
+            }
+        }
+
+        let rv = Revive { types: &target.types, named: &self.types };
+        ts += &rv.ts();
+
+        for (_, r) in self.route.iter() {
+            ts += &r.def_ts(target, &rv);
+        }
+
+        ts
+    }
+
+    fn generate_dart(&self, target: &Target) -> String {
+        let mut imports = String::new();
+        for (path, _) in self.format_imports(target) {
+            imports += &format!("\nimport '{path}';");
+        }
+        let mut aliases = String::new();
+        for (alias, base, format) in self.format_aliases(target) {
+            let ty =
+                format.map_or_else(|| base.dart(false), |f| f.name.clone());
+            aliases += &format!("\ntypedef {alias} = {ty};");
+        }
 
-            // let body = r.def_dart(false);
-            // let exp = format!("typedef {n} = {body};\n");
-            // dart.write_all(body.as_bytes())?;
-            // dart.write(b"\n")?;
+        let mut dart = formatdoc! {r#"
+            // DO NOT EDIT THIS FILE.
+            // THIS IS A GENERATED FILE AND ANY EDIT WILL BE REPLACED
+            // import * as ud from './user_defined';
+
+            import 'package:http/http.dart' as http;
+            import 'package:freezed_annotation/freezed_annotation.dart';{}
+
+            part 'gen.freezed.dart';
+            part 'gen.g.dart';
+
+            const API_VERSION = "{}";
+
+            typedef JsonObject = Map<String, dynamic>;{}
+
+
+        "#, imports, self.api_version, aliases};
+
+        for (_, r) in self.types.iter() {
+            dart += &r.def_dart(false);
+            dart.push('\n');
         }
 
         for (_, r) in self.route.iter() {
-            ts.write_all(r.def_ts().as_bytes())?;
-            // dart.write_all(r.def_dart().as_bytes())?;
+            dart += &r.def_dart(target);
         }
 
-        Ok(())
+        dart
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::openapi::docs::tests::docs;
+    use std::path::Path;
+
+    /// the output of the `target` toml for a spec without other files
+    pub(crate) fn generate(spec: &str, target: &str) -> String {
+        let path = Path::new("openapi.yaml");
+        let doc = crate::openapi::parse_document(path, spec).unwrap();
+        let mut docs = Docs::new(path, doc);
+        let mut diags = Diags::default();
+        let oa = OpenApi::from_document(docs.root(), false, &mut diags);
+        let asp = ApiSchema::from_openapi(
+            &oa,
+            &mut docs,
+            false,
+            HashSet::new(),
+            &mut diags,
+        );
+        assert!(!diags.has_errors(), "{:?}", diags.list);
+        asp.generate(&toml::from_str(target).unwrap())
+    }
+
+    /// messages of the errors found in the spec
+    fn errors(name: &str, files: &[(&str, &str)]) -> Vec<String> {
+        let (_dir, mut docs) = docs(name, files);
+        let mut diags = Diags::default();
+        let oa = OpenApi::from_document(docs.root(), false, &mut diags);
+        ApiSchema::from_openapi(
+            &oa,
+            &mut docs,
+            false,
+            HashSet::new(),
+            &mut diags,
+        );
+        diags.list.into_iter().map(|d| d.message).collect()
+    }
+
+    const COMMON: &str = "components: {schemas: {Money: {type: string}}}";
+
+    #[test]
+    fn reexported_schema_keeps_its_name() {
+        let spec = "
+            openapi: 3.1.0
+            info: {title: t, version: '1'}
+            paths: {}
+            components:
+              schemas:
+                Money: {$ref: './common.yaml#/components/schemas/Money'}
+                Order:
+                  type: object
+                  properties:
+                    a: {$ref: '#/components/schemas/Money'}
+                    b: {$ref: '../spec/common.yaml#/components/schemas/Money'}
+        ";
+        let files = [("spec/openapi.yaml", spec), ("spec/common.yaml", COMMON)];
+        assert_eq!(errors("reexport", &files), Vec::<String>::new());
+    }
+
+    #[test]
+    fn same_name_in_other_file_collides() {
+        let spec = "
+            openapi: 3.1.0
+            info: {title: t, version: '1'}
+            paths: {}
+            components:
+              schemas:
+                Money: {type: integer}
+                Order:
+                  type: object
+                  properties:
+                    a: {$ref: './common.yaml#/components/schemas/Money'}
+        ";
+        let files = [("openapi.yaml", spec), ("common.yaml", COMMON)];
+        let errors = errors("collide", &files);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].ends_with(
+            "./common.yaml#/components/schemas/Money has the same name as \
+             #/components/schemas/Money"
+        ));
     }
 }
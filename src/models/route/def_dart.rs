@@ -1,25 +1,35 @@
 use indoc::formatdoc;
 
-use crate::models::types::{ApiKind, ApiPrim};
+use crate::config::Target;
+use crate::models::types::{dart_ident, ApiKind, ApiPrim};
 
 use super::*;
 
+/// the type of an argument that can be left out
+fn nullable(ty: String, required: bool) -> String {
+    if required || ty.ends_with('?') || ty == "dynamic" {
+        return ty;
+    }
+    ty + "?"
+}
+
 impl ApiRoute {
-    pub fn def_dart(&self) -> String {
-        let (outy, http_out_type) = match &self.response_body {
+    pub fn def_dart(&self, target: &Target) -> String {
+        let name = target.naming.function(&self.name);
+        let (outy, http_out_type) = match self.response_body() {
             Some(ab) => match ab.content_type.as_str() {
-                "text/plain" => ("string".to_string(), "type: 'text',"),
+                ct if is_text(ct) => ("String".to_string(), "type: 'text',"),
                 "application/octet-stream" => {
-                    ("ArrayBuffer".to_string(), "type: 'arraybuffer',")
+                    ("List<int>".to_string(), "type: 'arraybuffer',")
                 }
-                "application/json" => {
+                ct if is_json(ct) => {
                     let Some(ty) = &ab.api_type else {
-                        panic!("json response body is none: {self:#?}");
+                        unreachable!("json response body is none");
                     };
 
                     (ty.ref_or_body_dart(false), "type: 'json',")
                 }
-                _ => panic!("unknown response type: {self:#?}"),
+                _ => unreachable!("unknown response type"),
             },
             None => ("void".to_string(), ""),
         };
@@ -33,14 +43,19 @@ impl ApiRoute {
             // pi.push_str("params: {");
 
             for p in self.params.iter() {
-                assert!(p.required);
                 // bloom_names.push(p.name.as_str());
 
                 if p.param_in.is_query() {
                     query_params.push(p.name.as_str());
                 }
 
-                input.push(format!("{} {}", p.api_type.ref_or_body_dart(true), p.name));
+                // `body` is the request body
+                let mut var = dart_ident(&p.name);
+                if var == "body" {
+                    var.push('_');
+                }
+                let ty = p.api_type.ref_or_body_dart(true);
+                input.push(format!("{} {var}", nullable(ty, p.required)));
 
                 // pi.push_str(&p.name);
                 // pi.push(':');
@@ -56,8 +71,9 @@ impl ApiRoute {
         let mut body = String::with_capacity(1024);
         // body.push_str("var data = void 0;");
 
-        if let Some(rb) = &self.request_body {
-            input.push(format!("{} body", rb.api_type.ref_or_body_dart(true)));
+        if let Some(rb) = self.request_body.first() {
+            let ty = rb.api_type.ref_or_body_dart(true);
+            input.push(format!("{} body", nullable(ty, rb.required)));
 
             if rb.content_type != "multipart/form-data" {
                 headers.push_str("'Content-Type': '");
@@ -67,16 +83,19 @@ impl ApiRoute {
 
             body.clear();
             match rb.content_type.as_str() {
-                "text/plain" => {
+                ct if is_text(ct) || ct == "application/octet-stream" => {
                     body.push_str("let data = body;");
                 }
-                "application/json" => {
+                ct if is_json(ct) => {
                     body.push_str("let data = JSON.stringify(body);");
                 }
+                "application/x-www-form-urlencoded" => {
+                    body.push_str("let data = new URLSearchParams(body);");
+                }
                 "multipart/form-data" => {
                     body.push_str("let data = new FormData();\n");
                     let ApiKind::Object(obj) = &rb.api_type.kind else {
-                        panic!("multipart body must be an object");
+                        unreachable!("multipart body must be an object");
                     };
 
                     // fn is_prim(ty: &ApiType) -> (bool, bool) {
@@ -130,7 +149,7 @@ impl ApiRoute {
                         "});
                     }
                 }
-                _ => panic!("unknown request_body: {self:#?}"),
+                _ => unreachable!("unknown request_body"),
             }
         }
 
@@ -152,8 +171,8 @@ impl ApiRoute {
             {doc}
             Promise<ud.Result<{outy}>>
             */
-            Future<{outy}> {} ({input}) async {{
-            return 0;
+            Future<{outy}> {name} ({input}) async {{
+            throw UnimplementedError('{name}');
             /*
                 // {{params_bloom}}
                 {body}
@@ -186,7 +205,6 @@ impl ApiRoute {
 
             }}
         "#,
-            self.name,
             doc = self.doc,
         }
     }
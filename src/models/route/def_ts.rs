@@ -1,45 +1,458 @@
 use indoc::formatdoc;
 
-use crate::models::types::{ApiKind, ApiPrim};
+use crate::config::Target;
+use crate::models::types::{ApiKind, ApiPrim, Revive};
 
 use super::*;
 
+/// javascript keywords that can not be used as variable names
+const RESERVED: &[&str] = &[
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "new",
+    "null",
+    "return",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "let",
+    "static",
+    "yield",
+    "await",
+    "data",
+    "r",
+    "ovh",
+    "override",
+    "params",
+    "body",
+    "cookie",
+    "query",
+    "accept",
+];
+
+/// a variable name for a parameter. e.g. `X-Tenant-Id` -> `x_tenant_id`
+fn ts_ident(name: &str) -> String {
+    let mut var: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if var.starts_with(|c: char| c.is_ascii_digit()) || var.is_empty() {
+        var.insert(0, '_');
+    }
+    if var != name {
+        var = var.to_lowercase();
+    }
+    if RESERVED.contains(&var.as_str()) {
+        var.push('_');
+    }
+    var
+}
+
 impl ApiRoute {
-    pub fn def_ts(&self) -> String {
-        let (outy, fetch_res) = match &self.response_body {
-            Some(ab) => match ab.content_type.as_str() {
-                "text/plain" => ("string".to_string(), "await r.text()"),
-                "application/octet-stream" => {
-                    ("ArrayBuffer".to_string(), "await r.arrayBuffer()")
+    /// type of a response body and the expression that reads it
+    fn content_ts(ab: &ApiResponseBody, rv: &Revive) -> (String, String) {
+        match ab.content_type.as_str() {
+            ct if is_text(ct) => {
+                ("string".to_string(), "await r.text()".to_string())
+            }
+            "application/octet-stream" => {
+                ("ArrayBuffer".to_string(), "await r.arrayBuffer()".to_string())
+            }
+            ct if is_json(ct) => {
+                let Some(ty) = &ab.api_type else {
+                    unreachable!("json response body is none");
+                };
+
+                let json = rv.parse(ty);
+                let fetch = match rv.expr(ty, false, false) {
+                    Some(e) => format!("_map({json}, (v: any) => {e})"),
+                    None => json,
+                };
+                (ty.ref_or_body_ts(false), fetch)
+            }
+            _ => unreachable!("unknown response type"),
+        }
+    }
+
+    /// type of a response and the expression that reads it, responses with
+    /// more than one media type are read by their `Content-Type` header
+    fn response_ts(
+        bodies: &[ApiResponseBody], rv: &Revive,
+    ) -> (String, String) {
+        let Some((first, rest)) = bodies.split_first() else {
+            return ("void".to_string(), "void 0 as void".to_string());
+        };
+
+        let (ty, fetch) = Self::content_ts(first, rv);
+        let mut types = vec![ty];
+        let mut read = String::new();
+        for ab in rest {
+            let (ty, fetch) = Self::content_ts(ab, rv);
+            if !types.contains(&ty) {
+                types.push(ty);
+            }
+            read += &format!(
+                "r.headers.get('Content-Type')?.startsWith('{}') ? {fetch} : ",
+                ab.content_type
+            );
+        }
+        read.push_str(&fetch);
+
+        (types.join(" | "), read)
+    }
+
+    /// type of a response header and the expression that parses its
+    /// value `v`, lists are comma separated
+    fn header_ts(at: &ApiType) -> (String, String) {
+        fn scalar(at: &ApiType) -> (&'static str, &'static str) {
+            match &at.kind {
+                ApiKind::Prim(ApiPrim::Option(at)) => scalar(at),
+                ApiKind::Prim(ApiPrim::Int | ApiPrim::Float) => {
+                    ("number", "Number(v)")
                 }
-                "application/json" => {
-                    let Some(ty) = &ab.api_type else {
-                        panic!("json response body is none: {self:#?}");
-                    };
+                ApiKind::Prim(ApiPrim::Bool) => ("boolean", "v == 'true'"),
+                _ => ("string", "v"),
+            }
+        }
+
+        match &at.kind {
+            ApiKind::Prim(ApiPrim::Option(at)) => Self::header_ts(at),
+            ApiKind::Array(item) => {
+                let (ty, parse) = scalar(item);
+                let parse = format!("v.split(/\\s*,\\s*/).map((v) => {parse})");
+                (format!("{ty}[]"), parse)
+            }
+            _ => {
+                let (ty, parse) = scalar(at);
+                (ty.to_string(), parse.to_string())
+            }
+        }
+    }
+
+    /// type of the status and the condition on `{var}.status` that
+    /// matches it, `default` matches every other status
+    fn status_ts(status: &str, var: &str) -> (String, Option<String>) {
+        match status.as_bytes() {
+            [c @ b'1'..=b'5', b'X', b'X'] => {
+                let s = (c - b'0') as u16 * 100;
+                let e = s + 100;
+                let cond = format!("{var}.status >= {s} && {var}.status < {e}");
+                ("number".to_string(), Some(cond))
+            }
+            b"default" => ("number".to_string(), None),
+            _ => {
+                (status.to_string(), Some(format!("{var}.status == {status}")))
+            }
+        }
+    }
+
+    /// type of a request body, raw bytes are sent as is
+    fn request_type_ts(rb: &ApiRequstBody) -> String {
+        if rb.content_type == "application/octet-stream" {
+            return "Blob | ArrayBuffer".to_string();
+        }
+        rb.api_type.ref_or_body_ts(true)
+    }
+
+    /// statements that put the body `src` into `data`,
+    /// `decl` is used to declare `data`
+    fn request_ts(
+        rb: &ApiRequstBody, src: &str, decl: &str, rv: &Revive,
+    ) -> String {
+        let mut body = String::with_capacity(1024);
+        // bigints are written by a replacer, `JSON.stringify` throws on them
+        let json = if rv.types.int64.is_converted() { ", _json" } else { "" };
+        let dumped = match rv.expr(&rb.api_type, true, false) {
+            Some(e) => format!("_map({src}, (v: any) => {e})"),
+            None => src.to_string(),
+        };
+        match rb.content_type.as_str() {
+            ct if is_text(ct) || ct == "application/octet-stream" => {
+                body += &format!("{decl}data = {src};");
+            }
+            ct if is_json(ct) => {
+                body +=
+                    &format!("{decl}data = JSON.stringify({dumped}{json});");
+            }
+            "application/x-www-form-urlencoded" => {
+                body += &formatdoc! {"
+                    {decl}data = new URLSearchParams();
+                    for (let [k, v] of Object.entries({dumped})) {{
+                        if (v == null) continue;
+                        for (let x of Array.isArray(v) ? v : [v]) {{
+                            data.append(k, String(x));
+                        }}
+                    }}"
+                };
+            }
+            "multipart/form-data" => {
+                body += &format!("{decl}data = new FormData();\n");
+                let ApiKind::Object(obj) = &rb.api_type.kind else {
+                    unreachable!("multipart body must be an object");
+                };
+
+                // fn is_prim(ty: &ApiType) -> (bool, bool) {
+                //     if let ApiKind::O(uni) = &ty.kind {
+                //         if uni.len() != 2 {
+                //             return (false, false);
+                //         }
+                //         let mut nullable = false;
+                //         let mut prim = false;
+                //         for at in uni {
+                //             if matches!(at.kind, ApiKind::Null) {
+                //                 nullable = true;
+                //             }
+                //
+                //             if matches!(
+                //                 at.kind,
+                //                 ApiKind::Str | ApiKind::File
+                //             ) {
+                //                 prim = true;
+                //             }
+                //         }
+                //         return (prim, nullable);
+                //     }
+                //     (matches!(ty.kind, ApiKind::Str | ApiKind::File), false)
+                // }
+
+                for (name, ty, _rq) in obj {
+                    // let (prim, nullable) = is_prim(ty);
+                    if let ApiKind::Prim(prim) = &ty.kind {
+                        if let ApiPrim::Option(_) = prim {
+                            body += &format!("{src}.{name} && ");
+                        }
+                        body += &format!("data.set('{name}', {src}.{name});\n");
+                        continue;
+                    }
+
+                    body.push_str(&formatdoc! {"
+                        data.set(
+                            '{name}',
+                            new Blob(
+                                [JSON.stringify({src}.{name}{json})],
+                                {{ type: 'application/json' }}
+                            )
+                        );\n
+                    "});
+                }
+            }
+            _ => unreachable!("unknown request_body"),
+        }
+        body
+    }
+
+    pub fn def_ts(&self, target: &Target, rv: &Revive) -> String {
+        let name = target.naming.function(&self.name);
+        let success = self.successes().next().map_or(&[][..], |s| &s.bodies);
+        let (mut outy, _) = Self::response_ts(success, rv);
 
-                    (ty.ref_or_body_ts(false), "await r.json()")
+        // the caller picks one of the media types of the successful
+        // response, with a single success the result is narrowed to it
+        let mut accept = None;
+        if let [first, ..] = success {
+            if success.len() > 1 {
+                let cts = Vec::from_iter(
+                    success.iter().map(|b| format!("'{}'", b.content_type)),
+                );
+                accept = Some((
+                    format!(
+                        "<A extends {} = '{}'>",
+                        cts.join(" | "),
+                        first.content_type
+                    ),
+                    format!("accept: A = '{}' as A", first.content_type),
+                ));
+                if self.successes().count() == 1 {
+                    let map = Vec::from_iter(success.iter().map(|b| {
+                        format!(
+                            "'{}': {}",
+                            b.content_type,
+                            Self::content_ts(b, rv).0
+                        )
+                    }));
+                    outy = format!("{{ {} }}[A]", map.join(", "));
                 }
-                _ => panic!("unknown response type: {self:#?}"),
-            },
-            None => ("void".to_string(), "void 0 as void"),
+            }
+        }
+
+        // bodies are read based on the status of the response,
+        // exact codes are checked before ranges like `4XX`
+        let (mut exact, mut ranges) = (Vec::new(), Vec::new());
+        let mut fallback = "await _unknown(r)".to_string();
+        let mut read = |status: &str, fetch: String| {
+            match Self::status_ts(status, "r") {
+                (_, Some(cond)) if status.ends_with("XX") => {
+                    ranges.push((cond, fetch))
+                }
+                (_, Some(cond)) => exact.push((cond, fetch)),
+                (_, None) => fallback = fetch,
+            };
         };
 
+        let mut oks = Vec::<String>::new();
+        let mut ok_types = Vec::<String>::new();
+        let mut ok_conds = Vec::<String>::new();
+        for s in self.successes() {
+            let (mut sty, sfetch) = Self::response_ts(&s.bodies, rv);
+            if accept.is_some() && oks.is_empty() {
+                sty = outy.clone();
+            }
+            let (status, cond) = Self::status_ts(&s.status, "this");
+            read(&s.status, sfetch);
+            ok_conds.extend(cond);
+            oks.push(format!("ud.Ok<{sty}, {status}>"));
+            if !ok_types.contains(&sty) {
+                ok_types.push(sty);
+            }
+        }
+
+        let mut errors = Vec::<String>::new();
+        for e in self.errors() {
+            let (ety, efetch) = Self::response_ts(&e.bodies, rv);
+            read(&e.status, efetch);
+            let (status, _) = Self::status_ts(&e.status, "r");
+            errors.push(format!("ud.Err<{status}, {ety}>"));
+        }
+        // statuses that are not documented can still be sent
+        if !errors.is_empty()
+            && self.responses.iter().all(|r| r.status != "default")
+        {
+            errors.push("ud.Err<number, unknown>".to_string());
+        }
+
+        // a single 200 response is typed as `ud.Ok<T>`
+        let single =
+            oks.len() <= 1 && self.successes().all(|s| s.status == "200");
+        let (outy, okty, ok_cond) = if single {
+            let okty = format!("ud.Ok<{outy}>");
+            (outy, okty, "this.status == 200".to_string())
+        } else {
+            (ok_types.join(" | "), oks.join(" | "), ok_conds.join(" || "))
+        };
+
+        let (mut result, errty) = if errors.is_empty() {
+            (format!("ud.Result<{outy}>"), "ud.Err".to_string())
+        } else {
+            let errty = errors.join(" | ");
+            (format!("ud.Result<{outy}, {errty}>"), errty)
+        };
+
+        // documented response headers are parsed when they are read
+        let mut headers_get = String::new();
+        let rhs = self.headers();
+        if !rhs.is_empty() {
+            let mut types = String::with_capacity(256);
+            let mut getters = String::with_capacity(1024);
+            for h in rhs {
+                let (ty, parse) = Self::header_ts(&h.api_type);
+                let name = &h.name;
+                types += &format!("'{name}': {ty} | null, ");
+                getters += &format!(
+                    "\n            get '{name}'() {{ let v = r.headers.get('{name}'); return v == null ? null : {parse} }},"
+                );
+            }
+            result = format!("{result} & {{ headers: {{ {types}}} }}");
+            headers_get =
+                format!("\n        headers: {{{getters}\n        }},");
+        }
+
+        let mut fetch_res = String::with_capacity(256);
+        for (cond, fetch) in exact.iter().chain(ranges.iter()) {
+            fetch_res += &format!("{cond} ? {fetch} : ");
+        }
+        fetch_res.push_str(&fallback);
+
         let mut input = Vec::<String>::with_capacity(10);
         let mut query_params = Vec::with_capacity(10);
         let mut bloom_names = Vec::with_capacity(10);
 
+        let mut headers = String::with_capacity(512);
+        let mut cookies = Vec::new();
+        let mut ts_url = self.url.replace('{', "${");
+
         if !self.params.is_empty() {
             let mut pi = String::with_capacity(512);
             pi.push_str("params: {");
 
             for p in self.params.iter() {
-                bloom_names.push(p.name.as_str());
+                let name = p.name.as_str();
+                let var = ts_ident(name);
+                // names like `X-Tenant-Id` are renamed when destructured
+                let (key, field) = if var == name {
+                    (name.to_string(), name.to_string())
+                } else {
+                    let key = format!("'{name}'");
+                    let field = format!("{key}: {var}");
+                    (key, field)
+                };
 
-                if p.param_in.is_query() {
-                    query_params.push(p.name.as_str());
+                let style = p.style.as_str();
+                let explode = p.explode;
+                // dates and bytes are sent the way they are in json
+                let value = match rv.expr(&p.api_type, true, false) {
+                    Some(e) => format!("_map({var}, (v: any) => {e})"),
+                    None => var.clone(),
+                };
+                match p.param_in {
+                    ApiParamIn::Query => {
+                        let reserved = if p.allow_reserved { ", true" } else { "" };
+                        query_params.push(format!(
+                            "_param('{name}', {value}, '{style}', {explode}{reserved})"
+                        ));
+                    }
+                    ApiParamIn::Path => {
+                        ts_url = ts_url.replace(
+                            &format!("${{{name}}}"),
+                            &format!("${{_param('{name}', {value}, '{style}', {explode})}}"),
+                        )
+                    }
+                    // header values are sent as they are, not url encoded
+                    ApiParamIn::Header if p.required => {
+                        headers += &format!(
+                            "'{name}': _param('{name}', {value}, '{style}', {explode}, false, true),"
+                        );
+                    }
+                    ApiParamIn::Header => {
+                        headers += &format!(
+                            "...({var} != null && {{ '{name}': _param('{name}', {value}, '{style}', {explode}, false, true) }}),"
+                        );
+                    }
+                    // exploded cookies are one `name=value` pair each
+                    ApiParamIn::Cookie => cookies.push(format!(
+                        "_param('{name}', {value}, '{style}', {explode}).split('&').join('; ')"
+                    )),
                 }
+                bloom_names.push(field);
 
-                pi.push_str(&p.name);
+                pi.push_str(&key);
                 if !p.required {
                     pi.push('?');
                 }
@@ -52,106 +465,96 @@ impl ApiRoute {
             input.push(pi);
         }
 
-        let mut headers = String::with_capacity(512);
-        let mut body = String::with_capacity(1024);
-        body.push_str("let data = void 0;");
-
-        if let Some(rb) = &self.request_body {
-            input.push(format!("body: {}", rb.api_type.ref_or_body_ts(true)));
+        let mut body = "let data = void 0;".to_string();
 
-            if rb.content_type != "multipart/form-data" {
-                headers.push_str("'Content-Type': '");
-                headers.push_str(&rb.content_type);
-                headers.push_str("',");
-            }
-
-            body.clear();
-            match rb.content_type.as_str() {
-                "text/plain" => {
-                    body.push_str("let data = body;");
+        // optional bodies are only sent when they are given
+        let opt = if self.body_required() { "" } else { "?" };
+        match self.request_body.as_slice() {
+            [] => {}
+            [rb] if rb.required => {
+                input.push(format!("body: {}", Self::request_type_ts(rb)));
+                if rb.content_type != "multipart/form-data" {
+                    headers.push_str("'Content-Type': '");
+                    headers.push_str(&rb.content_type);
+                    headers.push_str("',");
                 }
-                "application/json" => {
-                    body.push_str("let data = JSON.stringify(body);");
+                body = Self::request_ts(rb, "body", "let ", rv);
+            }
+            [rb] => {
+                input.push(format!("body?: {}", Self::request_type_ts(rb)));
+                if rb.content_type != "multipart/form-data" {
+                    headers += &format!(
+                        "...(body != null && {{ 'Content-Type': '{}' }}),",
+                        rb.content_type
+                    );
                 }
-                "multipart/form-data" => {
-                    body.push_str("let data = new FormData();\n");
-                    let ApiKind::Object(obj) = &rb.api_type.kind else {
-                        panic!("multipart body must be an object");
-                    };
-
-                    // fn is_prim(ty: &ApiType) -> (bool, bool) {
-                    //     if let ApiKind::O(uni) = &ty.kind {
-                    //         if uni.len() != 2 {
-                    //             return (false, false);
-                    //         }
-                    //         let mut nullable = false;
-                    //         let mut prim = false;
-                    //         for at in uni {
-                    //             if matches!(at.kind, ApiKind::Null) {
-                    //                 nullable = true;
-                    //             }
-                    //
-                    //             if matches!(
-                    //                 at.kind,
-                    //                 ApiKind::Str | ApiKind::File
-                    //             ) {
-                    //                 prim = true;
-                    //             }
-                    //         }
-                    //         return (prim, nullable);
-                    //     }
-                    //     (matches!(ty.kind, ApiKind::Str | ApiKind::File), false)
-                    // }
-
-                    for (name, ty, _rq) in obj {
-                        // let (prim, nullable) = is_prim(ty);
-                        if let ApiKind::Prim(prim) = &ty.kind {
-                            if let ApiPrim::Option(_) = prim {
-                                body.push_str("body.");
-                                body.push_str(name);
-                                body.push_str(" && ");
-                            }
-                            body.push_str("data.set('");
-                            body.push_str(name);
-                            body.push_str("', body.");
-                            body.push_str(name);
-                            body.push_str(");\n");
-                            continue;
-                        }
+                body = format!(
+                    "let data;\nif (body != null) {{\n{}\n}}",
+                    Self::request_ts(rb, "body", "", rv)
+                );
+            }
+            // the caller picks the media type of the body
+            rbs => {
+                let variants = Vec::from_iter(rbs.iter().map(|rb| {
+                    let ty = Self::request_type_ts(rb);
+                    format!("{{ type: '{}', value: {ty} }}", rb.content_type)
+                }));
+                input.push(format!("body{opt}: {}", variants.join(" | ")));
+                headers.push_str(
+                    "...(body && body.type != 'multipart/form-data' && { 'Content-Type': body.type }),",
+                );
 
-                        body.push_str(&formatdoc! {"
-                            data.set(
-                                '{name}',
-                                new Blob(
-                                    [JSON.stringify(body.{name})],
-                                    {{ type: 'application/json' }}
-                                )
-                            );\n
-                        "});
-                    }
+                body = format!("let data;\nswitch (body{opt}.type) {{\n");
+                for rb in rbs {
+                    body += &format!(
+                        "case '{}': {{\n{}\nbreak\n}}\n",
+                        rb.content_type,
+                        Self::request_ts(rb, "body.value", "", rv)
+                    );
                 }
-                _ => panic!("unknown request_body: {self:#?}"),
+                body.push('}');
             }
         }
 
+        if !query_params.is_empty() {
+            body += &format!(
+                "\nlet query = [{}].filter(Boolean).join('&');",
+                query_params.join(", ")
+            );
+            ts_url.push_str("${query && '?' + query}");
+        }
+
+        if !cookies.is_empty() {
+            body += &format!(
+                "\nlet cookie = [{}].filter(Boolean).join('; ');",
+                cookies.join(", ")
+            );
+            headers.push_str("...(cookie && { Cookie: cookie }),");
+        }
+
+        let mut generics = String::new();
+        if let Some((g, param)) = accept {
+            generics = g;
+            input.push(param);
+            headers.push_str("'Accept': accept,");
+        }
+
         input.push("override: Partial<ud.HttpxProps> = {}".to_string());
 
         let input = input.join(", ");
-        let ts_url = self.url.replace('{', "${");
 
         let params_bloom = if !bloom_names.is_empty() {
             format!("let {{ {} }} = params;", bloom_names.join(","))
         } else {
             String::new()
         };
-        let query_params = query_params.join(",");
         let method_upper = self.method.to_uppercase();
 
         formatdoc! {r#"
             /**
             {doc}
             */
-            export async function {} ({input}) : Promise<ud.Result<{outy}>> {{
+            export async function {name}{generics} ({input}) : Promise<{result}> {{
                 {params_bloom}
                 {body}
                 let ovh = override.headers || {{}};
@@ -159,7 +562,6 @@ impl ApiRoute {
                 let r = await ud.httpx({{
                     url: `{ts_url}`,
                     method: '{method_upper}',
-                    params: {{ {query_params} }},
                     headers: {{ {headers} ...ovh }},
                     data,
                     ...override
@@ -168,19 +570,72 @@ impl ApiRoute {
                 return {{
                     r: r.clone(),
                     status: r.status,
-                    body: {fetch_res},
-                    ok(): this is ud.Ok<{outy}> {{
-                        return this.status == 200
+                    body: {fetch_res},{headers_get}
+                    ok(): this is {okty} {{
+                        return {ok_cond}
                     }},
-                    err(): this is ud.Err {{
+                    err(): this is {errty} {{
                         return !this.ok()
                     }},
-                }} as ud.Result<{outy}>
+                }} as {result}
 
             }}
         "#,
-            self.name,
             doc = self.doc,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::models::tests::generate;
+
+    const TS: &str = "lang = 'ts'\noutput = 'gen.ts'";
+
+    #[test]
+    fn params_by_style_and_explode() {
+        let spec = "
+            openapi: 3.1.0
+            info: {title: t, version: '1'}
+            paths:
+              /a/{id}/{tags}:
+                get:
+                  parameters:
+                    - {name: id, in: path, required: true, schema: {type: integer}}
+                    - name: tags
+                      in: path
+                      required: true
+                      style: matrix
+                      explode: true
+                      schema: {type: array, items: {type: string}}
+                    - {name: q, in: query, schema: {type: array, items: {type: string}}}
+                    - name: ids
+                      in: query
+                      explode: false
+                      schema: {type: array, items: {type: integer}}
+                    - name: filter
+                      in: query
+                      style: deepObject
+                      schema: {type: object}
+                    - {name: raw, in: query, allowReserved: true, schema: {type: string}}
+                    - {name: X-Ids, in: header, schema: {type: array, items: {type: integer}}}
+                    - {name: sid, in: cookie, required: true, schema: {type: string}}
+                  responses: {'204': {description: ok}}
+        ";
+        let ts = generate(spec, TS);
+
+        // path parameters are `simple` and query parameters `form` at first
+        for call in [
+            "${_param('id', id, 'simple', false)}",
+            "${_param('tags', tags, 'matrix', true)}",
+            "_param('q', q, 'form', true)",
+            "_param('ids', ids, 'form', false)",
+            "_param('filter', filter, 'deepObject', false)",
+            "_param('raw', raw, 'form', true, true)",
+            "'X-Ids': _param('X-Ids', x_ids, 'simple', false, false, true)",
+            "_param('sid', sid, 'form', true).split('&').join('; ')",
+        ] {
+            assert!(ts.contains(call), "{call} is not in\n{ts}");
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use crate::openapi::path::ParameterIn;
+use crate::openapi::path::{ParameterIn, ParameterStyle};
 
 use super::types::ApiType;
 
-mod def_ts;
 mod def_dart;
+mod def_ts;
 mod parse;
 
 #[derive(Debug, Clone)]
@@ -12,17 +12,53 @@ pub struct ApiRoute {
     pub name: String,
     pub params: Vec<ApiParam>,
     pub url: String,
-    pub request_body: Option<ApiRequstBody>,
-    pub response_body: Option<ApiResponseBody>,
+    /// every media type the body can be sent as, the first is the default
+    pub request_body: Vec<ApiRequstBody>,
+    pub responses: Vec<ApiResponse>,
     pub method: String,
 }
 
+impl ApiRoute {
+    pub fn body_required(&self) -> bool {
+        self.request_body.first().is_some_and(|rb| rb.required)
+    }
+
+    /// body of the first successful response
+    pub fn response_body(&self) -> Option<&ApiResponseBody> {
+        self.successes().next()?.bodies.first()
+    }
+
+    /// the `2xx` responses
+    pub fn successes(&self) -> impl Iterator<Item = &ApiResponse> {
+        self.responses.iter().filter(|r| r.is_success())
+    }
+
+    /// headers of every response, the first one wins for duplicate names
+    pub fn headers(&self) -> Vec<&ApiHeader> {
+        let mut headers = Vec::<&ApiHeader>::new();
+        for h in self.responses.iter().flat_map(|r| r.headers.iter()) {
+            if !headers.iter().any(|x| x.name.eq_ignore_ascii_case(&h.name)) {
+                headers.push(h);
+            }
+        }
+        headers
+    }
+
+    /// every documented response except the successful ones
+    pub fn errors(&self) -> impl Iterator<Item = &ApiResponse> {
+        self.responses.iter().filter(|r| !r.is_success())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ApiParam {
     pub name: String,
     pub param_in: ApiParamIn,
     pub required: bool,
     pub api_type: ApiType,
+    pub style: ParameterStyle,
+    pub explode: bool,
+    pub allow_reserved: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -62,14 +98,48 @@ impl From<ParameterIn> for ApiParamIn {
     }
 }
 
+/// `status` is a code like `404`, a range like `4XX` or `default`
+#[derive(Debug, Clone)]
+pub struct ApiResponse {
+    pub status: String,
+    /// one for every media type of the response
+    pub bodies: Vec<ApiResponseBody>,
+    pub headers: Vec<ApiHeader>,
+}
+
+/// a documented response header, e.g. `X-Total-Count`
+#[derive(Debug, Clone)]
+pub struct ApiHeader {
+    pub name: String,
+    pub api_type: ApiType,
+}
+
+impl ApiResponse {
+    pub fn is_success(&self) -> bool {
+        self.status.starts_with('2')
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ApiResponseBody {
     pub content_type: String,
     pub api_type: Option<ApiType>,
 }
 
+/// `application/json` and vendor types like `application/vnd.api+json`
+pub fn is_json(content_type: &str) -> bool {
+    content_type == "application/json" || content_type.ends_with("+json")
+}
+
+/// `text/plain`, `text/csv` and other text types
+pub fn is_text(content_type: &str) -> bool {
+    content_type.starts_with("text/")
+}
+
 #[derive(Debug, Clone)]
 pub struct ApiRequstBody {
     pub content_type: String,
     pub api_type: ApiType,
+    /// `false` when the body can be left out
+    pub required: bool,
 }
@@ -1,114 +1,265 @@
-use indexmap::IndexMap;
-
 use crate::{
-    models::types::ApiKind,
-    openapi::{
-        common::{OaSchema, RefOr},
-        path::{Operation, RequestBody, Response},
+    diag::ptr,
+    models::{
+        types::{ApiKind, ApiPrim},
+        Ctx,
+    },
+    openapi::path::{
+        Content, Operation, Parameter, ParameterStyle, PathItem, RequestBody,
+        Response,
     },
 };
 
 use super::*;
 
 impl ApiRoute {
+    /// `loc` is the location of the path item `op` belongs to
     pub fn parse_openapi(
-        url: &str, method: &str, op: &Operation,
-        types: &mut IndexMap<String, ApiType>,
-        schemas: &IndexMap<String, RefOr<OaSchema>>,
+        url: &str, method: &str, pi: &PathItem, op: &Operation, loc: &str,
+        ctx: &mut Ctx,
     ) -> Self {
-        let res = match op.responses.get("200") {
-            Some(RefOr::T(t)) => Some(t),
-            _ => None,
-        };
-
+        let path_loc = loc;
+        let loc = &ptr(loc, method);
         // println!("url: [{method}] {url}");
-        let rb = ApiResponseBody::parse_openapi(res, types, schemas);
+        let mut responses = Vec::with_capacity(op.responses.len());
+        for (status, r) in op.responses.iter() {
+            let rloc = ptr(&ptr(loc, "responses"), status);
+            let Some((r, doc, rloc)) = ctx.resolve(r, &rloc) else {
+                continue;
+            };
+            let bodies = ctx.with_base(doc, |ctx| {
+                ApiResponseBody::parse_openapi(&r, &rloc, ctx)
+            });
+            let headers = ctx
+                .with_base(doc, |ctx| ApiHeader::parse_openapi(&r, &rloc, ctx));
+            responses.push(ApiResponse {
+                status: status.to_string(),
+                bodies,
+                headers,
+            });
+        }
+
+        let rb = responses.iter().find(|r| r.is_success());
         let is_list = 'a: {
-            let Some(rb) = &rb else { break 'a false };
+            let Some(rb) = rb.and_then(|r| r.bodies.first()) else {
+                break 'a false;
+            };
             let Some(ty) = &rb.api_type else { break 'a false };
             matches!(ty.kind, ApiKind::Array(_))
         };
         let name = op.url_to_name(url, method, is_list);
 
-        let mut params = Vec::with_capacity(10);
-        if let Some(prs) = &op.parameters {
-            for p in prs {
-                let Some(psh) = &p.schema else {
-                    panic!("param with no schema: {op:#?}");
-                };
-
-                params.push(ApiParam {
-                    name: p.name.to_string(),
-                    required: p.required,
-                    param_in: p.parameter_in.into(),
-                    api_type: ApiType::parse_openapi(
+        // path level parameters first, operation level ones replace
+        // the parameters with the same name and location
+        let mut resolved = Vec::<(Parameter, usize, String)>::new();
+        let sources = [(&pi.parameters, path_loc), (&op.parameters, loc)];
+        for (prs, loc) in sources {
+            let Some(prs) = prs else { continue };
+            for (i, p) in prs.iter().enumerate() {
+                let ploc = ptr(&ptr(loc, "parameters"), i);
+                let Some(p) = ctx.resolve(p, &ploc) else { continue };
+                let same = resolved.iter_mut().find(|(o, ..)| {
+                    o.name == p.0.name && o.parameter_in == p.0.parameter_in
+                });
+                match same {
+                    Some(o) => *o = p,
+                    None => resolved.push(p),
+                }
+            }
+        }
+
+        let mut params = Vec::with_capacity(resolved.len());
+        for (p, doc, ploc) in resolved {
+            let Some(psh) = &p.schema else {
+                ctx.diags.error(
+                    &ploc,
+                    format!("parameter {} has no schema", p.name),
+                );
+                continue;
+            };
+
+            let pin = p.parameter_in;
+            let style = p.style.unwrap_or(ParameterStyle::default_for(pin));
+            if !style.allowed_in(pin) {
+                ctx.diags.error(
+                    &ptr(&ploc, "style"),
+                    format!(
+                        "style {} can not be used in {pin:?}",
+                        style.as_str()
+                    ),
+                );
+                continue;
+            }
+
+            params.push(ApiParam {
+                name: p.name.to_string(),
+                required: p.required,
+                param_in: p.parameter_in.into(),
+                api_type: ctx.with_base(doc, |ctx| {
+                    ApiType::parse_openapi(
                         None,
                         psh,
+                        &ptr(&ploc, "schema"),
                         Default::default(),
-                        types,
-                        schemas,
-                    ),
-                });
-            }
+                        ctx,
+                    )
+                }),
+                style,
+                explode: p.explode.unwrap_or(style == ParameterStyle::Form),
+                allow_reserved: p.allow_reserved,
+            });
         }
 
-        let ar = Self {
-            request_body: ApiRequstBody::parse_openapi(
-                &op.request_body,
-                types,
-                schemas,
-            ),
+        let request_body = op.request_body.as_ref().map_or(vec![], |rb| {
+            let rloc = ptr(loc, "requestBody");
+            let Some((rb, doc, rloc)) = ctx.resolve(rb, &rloc) else {
+                return vec![];
+            };
+            ctx.with_base(doc, |ctx| {
+                ApiRequstBody::parse_openapi(&rb, &rloc, ctx)
+            })
+        });
+
+        Self {
+            request_body,
             url: url.to_string(),
             name,
             params,
             method: method.to_string(),
-            response_body: rb,
+            responses,
             doc: format!(
                 "{}\n{}",
-                op.summary.as_ref().map(|v| v.as_str()).unwrap_or_default(),
-                op.description.as_ref().map(|v| v.as_str()).unwrap_or_default(),
+                op.summary.as_deref().unwrap_or_default(),
+                op.description.as_deref().unwrap_or_default(),
             ),
-        };
-
-        ar
+        }
     }
 }
 
 impl ApiRequstBody {
+    /// one for every media type of the body
     pub fn parse_openapi(
-        rb: &Option<RequestBody>, types: &mut IndexMap<String, ApiType>,
-        schemas: &IndexMap<String, RefOr<OaSchema>>,
-    ) -> Option<Self> {
-        let Some(rb) = rb else { return None };
-        assert!(rb.required.unwrap_or_default());
-        assert!(!rb.content.is_empty());
-        let (ct, c) = rb.content.iter().next().unwrap();
+        rb: &RequestBody, loc: &str, ctx: &mut Ctx,
+    ) -> Vec<Self> {
+        if rb.content.is_empty() {
+            ctx.diags
+                .error(&ptr(loc, "content"), "request body has no content");
+        }
 
-        let cs = c.schema.as_ref().unwrap();
+        let cloc = ptr(loc, "content");
+        let required = rb.required.unwrap_or_default();
+        rb.content
+            .iter()
+            .filter_map(|(ct, c)| {
+                Self::parse_content(ct, c, required, &ptr(&cloc, ct), ctx)
+            })
+            .collect()
+    }
 
-        Some(Self {
-            content_type: ct.to_string(),
-            api_type: ApiType::parse_openapi(
+    fn parse_content(
+        ct: &str, c: &Content, required: bool, loc: &str, ctx: &mut Ctx,
+    ) -> Option<Self> {
+        let form = matches!(
+            ct,
+            "multipart/form-data" | "application/x-www-form-urlencoded"
+        );
+        if !form
+            && !is_json(ct)
+            && !is_text(ct)
+            && ct != "application/octet-stream"
+        {
+            ctx.diags.error(loc, format!("unknown request body type: {ct}"));
+            return None;
+        }
+
+        let api_type = match &c.schema {
+            Some(cs) => ApiType::parse_openapi(
                 None,
                 cs,
+                &ptr(loc, "schema"),
                 Default::default(),
-                types,
-                schemas,
+                ctx,
             ),
-        })
+            // the raw bytes of the body
+            None if ct == "application/octet-stream" => {
+                ApiType::new(None, ApiPrim::File.into())
+            }
+            None => {
+                ctx.diags.error(loc, "request body has no schema");
+                return None;
+            }
+        };
+
+        if form && !matches!(api_type.kind, ApiKind::Object(_)) {
+            ctx.diags.error(loc, "form body must be an object");
+            return None;
+        }
+
+        Some(Self { content_type: ct.to_string(), api_type, required })
+    }
+}
+
+impl ApiHeader {
+    /// headers of the response, `Content-Type` is ignored
+    pub fn parse_openapi(r: &Response, loc: &str, ctx: &mut Ctx) -> Vec<Self> {
+        let hloc = ptr(loc, "headers");
+        let mut headers = Vec::with_capacity(r.headers.len());
+        for (name, h) in r.headers.iter() {
+            if name.eq_ignore_ascii_case("content-type") {
+                continue;
+            }
+            let Some((h, doc, hloc)) = ctx.resolve(h, &ptr(&hloc, name)) else {
+                continue;
+            };
+            // headers described by `content` are read as they are
+            let Some(schema) = &h.schema else {
+                let api_type = ApiType::new(None, ApiPrim::Str.into());
+                headers.push(Self { name: name.to_string(), api_type });
+                continue;
+            };
+            let api_type = ctx.with_base(doc, |ctx| {
+                ApiType::parse_openapi(
+                    None,
+                    schema,
+                    &ptr(&hloc, "schema"),
+                    Default::default(),
+                    ctx,
+                )
+            });
+            headers.push(Self { name: name.to_string(), api_type });
+        }
+        headers
     }
 }
 
 impl ApiResponseBody {
-    pub fn parse_openapi(
-        res: Option<&Response>, types: &mut IndexMap<String, ApiType>,
-        schemas: &IndexMap<String, RefOr<OaSchema>>,
+    /// one for every media type of the response
+    pub fn parse_openapi(r: &Response, loc: &str, ctx: &mut Ctx) -> Vec<Self> {
+        let cloc = ptr(loc, "content");
+        r.content
+            .iter()
+            .filter_map(|(ct, c)| {
+                Self::parse_content(ct, c, &ptr(&cloc, ct), ctx)
+            })
+            .collect()
+    }
+
+    fn parse_content(
+        ct: &str, c: &Content, loc: &str, ctx: &mut Ctx,
     ) -> Option<Self> {
-        let Some(r) = res else { return None };
-        if r.content.is_empty() {
-            return None;
+        match ct {
+            "application/octet-stream" => {}
+            ct if is_text(ct) => {}
+            ct if is_json(ct) && c.schema.is_none() => {
+                ctx.diags.error(loc, "json response has no schema");
+                return None;
+            }
+            ct if is_json(ct) => {}
+            _ => {
+                ctx.diags.error(loc, format!("unknown response type: {ct}"));
+                return None;
+            }
         }
-        let (ct, c) = r.content.iter().next().unwrap();
 
         Some(Self {
             content_type: ct.to_string(),
@@ -116,9 +267,9 @@ impl ApiResponseBody {
                 ApiType::parse_openapi(
                     None,
                     v,
+                    &ptr(loc, "schema"),
                     Default::default(),
-                    types,
-                    schemas,
+                    ctx,
                 )
             }),
         })
@@ -7,11 +7,17 @@ impl ApiPrim {
     pub fn ts(&self, for_input: bool) -> String {
         match self {
             ApiPrim::Null => "null".to_string(),
-            ApiPrim::Str => "string".to_string(),
+            ApiPrim::Str | ApiPrim::Uuid => "string".to_string(),
+            ApiPrim::DateTime => "_DateTime".to_string(),
+            ApiPrim::Date => "_Date".to_string(),
+            ApiPrim::Byte => "_Byte".to_string(),
             ApiPrim::Int => "number".to_string(),
+            ApiPrim::Int64 => "_Int64".to_string(),
             ApiPrim::Float => "number".to_string(),
             ApiPrim::File => "File".to_string(),
             ApiPrim::Bool => "boolean".to_string(),
+            ApiPrim::Any => "unknown".to_string(),
+            ApiPrim::Format(alias) => alias.clone(),
             ApiPrim::Option(opt) => {
                 format!("({} | null)", opt.ref_or_body_ts(for_input))
             }
@@ -21,11 +27,17 @@ impl ApiPrim {
     pub fn dart(&self, for_input: bool) -> String {
         match self {
             ApiPrim::Null => "null".to_string(),
-            ApiPrim::Str => "String".to_string(),
-            ApiPrim::Int => "int".to_string(),
+            ApiPrim::Str | ApiPrim::Uuid | ApiPrim::Byte => {
+                "String".to_string()
+            }
+            ApiPrim::DateTime | ApiPrim::Date => "DateTime".to_string(),
+            // dart integers are 64 bit
+            ApiPrim::Int | ApiPrim::Int64 => "int".to_string(),
             ApiPrim::Float => "double".to_string(),
             ApiPrim::File => "http.MultipartFile".to_string(),
             ApiPrim::Bool => "bool".to_string(),
+            ApiPrim::Any => "dynamic".to_string(),
+            ApiPrim::Format(alias) => alias.clone(),
             ApiPrim::Option(opt) => {
                 format!("{}?", opt.ref_or_body_dart(for_input))
             }
@@ -33,6 +45,123 @@ impl ApiPrim {
     }
 }
 
+impl ApiLiteral {
+    pub fn ts(&self) -> String {
+        match self {
+            Self::Str(s) => serde_json::to_string(s).unwrap_or_default(),
+            Self::Int(i) => i.to_string(),
+            Self::Float(f) => f.to_string(),
+            Self::Bool(b) => b.to_string(),
+        }
+    }
+
+    pub fn dart(&self) -> String {
+        match self {
+            Self::Str(s) => {
+                let s = s
+                    .replace('\\', "\\\\")
+                    .replace('\'', "\\'")
+                    .replace('$', "\\$");
+                format!("'{s}'")
+            }
+            _ => self.ts(),
+        }
+    }
+
+    /// name of the member for the literal, e.g. `V_2` for `-2`
+    pub fn member(&self) -> String {
+        match self {
+            Self::Str(s) => enum_ident(&s.to_uppercase()),
+            Self::Int(_) | Self::Float(_) => {
+                format!("V{}", self.ts().replace(['-', '.'], "_"))
+            }
+            Self::Bool(b) => b.to_string().to_uppercase(),
+        }
+    }
+
+    /// the type all the literals share in dart
+    pub fn dart_type(lits: &[Self]) -> &'static str {
+        if lits.iter().all(|l| matches!(l, Self::Int(_))) {
+            "int"
+        } else if lits
+            .iter()
+            .all(|l| matches!(l, Self::Int(_) | Self::Float(_)))
+        {
+            "num"
+        } else if lits.iter().all(|l| matches!(l, Self::Bool(_))) {
+            "bool"
+        } else {
+            "Object"
+        }
+    }
+}
+
+/// whether `name` can be a property name in typescript without quotes
+fn is_ts_ident(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// `value` as an identifier, e.g. `IN_PROGRESS` for `IN-PROGRESS` and
+/// `V2FA` for `2FA`
+pub fn enum_ident(value: &str) -> String {
+    let mut out: String = value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, 'V');
+    }
+    out
+}
+
+impl ApiEnumDoc {
+    /// names of the members, `x-enum-varnames` when given and `values`
+    /// otherwise. names that are taken get a number
+    pub fn members(&self, values: impl Iterator<Item = String>) -> Vec<String> {
+        let names: Vec<String> = if self.varnames.is_empty() {
+            values.collect()
+        } else {
+            self.varnames.iter().map(|v| enum_ident(v)).collect()
+        };
+
+        let mut out = Vec::<String>::with_capacity(names.len());
+        for name in names {
+            let mut member = name.clone();
+            let mut n = 1;
+            while out.contains(&member) {
+                n += 1;
+                member = format!("{name}_{n}");
+            }
+            out.push(member);
+        }
+        out
+    }
+
+    pub fn description(&self, i: usize) -> Option<&str> {
+        self.descriptions.get(i).map(String::as_str).filter(|d| !d.is_empty())
+    }
+}
+
+/// a doc comment at `indent`, `*/` can not be in it
+fn ts_doc(desc: &str, indent: &str) -> String {
+    let desc = desc.replace("*/", "*\\/");
+    let mut lines = desc.lines();
+    match (lines.next(), lines.next()) {
+        (Some(line), None) => format!("{indent}/** {line} */\n"),
+        _ => {
+            let mut out = format!("{indent}/**\n");
+            for line in desc.lines() {
+                out += &format!("{indent} * {line}\n");
+            }
+            out + &format!("{indent} */\n")
+        }
+    }
+}
+
 pub fn pascal_to_snake(value: &str) -> String {
     let mut out = String::with_capacity(value.len() * 2);
     for (i, ch) in value.chars().enumerate() {
@@ -56,48 +185,44 @@ impl ApiType {
             ApiKind::Unknown => panic!("unknown type: {self:?}"),
             ApiKind::Ref(r) => r.to_string(),
             ApiKind::Recursive => unreachable!("recursive"),
-            ApiKind::StrEnum(e) => {
-                let mut out = String::with_capacity(1024);
-                let len = e.len();
-                for (i, se) in e.iter().enumerate() {
-                    out.push('"');
-                    out.push_str(se);
-                    out.push('"');
-                    if i + 1 != len {
-                        out.push('|');
-                    }
-                }
-
-                if def && self.name.is_some() {
+            ApiKind::StrEnum(e, _) => {
+                let lits = Vec::from_iter(
+                    e.iter().map(|se| ApiLiteral::Str(se.clone()).ts()),
+                );
+                self.enum_ts(&lits, "string", def)
+            }
+            ApiKind::Enum(e, _) => {
+                let lits = Vec::from_iter(e.iter().map(ApiLiteral::ts));
+                self.enum_ts(&lits, "unknown", def)
+            }
+            ApiKind::Array(a) => format!("({}[])", a.ref_or_body_ts(for_input)),
+            ApiKind::Union(u) => u
+                .iter()
+                .map(|v| v.ref_or_body_ts(for_input))
+                .collect::<Vec<_>>()
+                .join("|"),
+            ApiKind::Tagged(key, t) => {
+                let mut out = t
+                    .iter()
+                    .map(|(tag, v)| {
+                        let v = v.ref_or_body_ts(for_input);
+                        format!("({{ '{key}':\"{tag}\" }}&{v})")
+                    })
+                    .collect::<Vec<_>>()
+                    .join("|");
+
+                if let (true, Some(name)) = (def, &self.name) {
                     out.push_str(";\n");
-                    let name = self.name.as_ref().unwrap();
-                    let snake = pascal_to_snake(name);
-                    let cname = snake.to_uppercase();
-                    out.push_str("export const ");
-                    out.push_str(&cname);
-                    out.push_str(" = [");
-                    for se in e.iter() {
-                        out.push('"');
-                        out.push_str(se);
-                        out.push_str(r#"","#);
-                    }
-                    out.push_str("] as const;\n");
-                    let lname = snake.to_lowercase();
+                    let lname = pascal_to_snake(name).to_lowercase();
                     out += &formatdoc! {"
-                    export function is_{lname}(value: string): value is {name} {{
-                        return {cname}.includes(value as {name})
+                    export function is_{lname}<_K extends {name}['{key}']>(value: {name}, tag: _K): value is Extract<{name}, {{ '{key}': _K }}> {{
+                        return value['{key}'] == tag
                     }}
                     "};
                 }
 
                 out
             }
-            ApiKind::Array(a) => format!("({}[])", a.ref_or_body_ts(for_input)),
-            ApiKind::Union(u) => u
-                .iter()
-                .map(|v| v.ref_or_body_ts(for_input))
-                .collect::<Vec<_>>()
-                .join("|"),
             ApiKind::Combo(c) => c
                 .iter()
                 .map(|v| v.ref_or_body_ts(for_input))
@@ -109,7 +234,12 @@ impl ApiType {
             ApiKind::Object(o) => {
                 let mut inner = String::with_capacity(1024);
                 for (p, v, rq) in o {
-                    inner.push_str(p);
+                    // names like `pet-kind` need quotes
+                    if is_ts_ident(p) {
+                        inner.push_str(p);
+                    } else {
+                        inner += &format!("'{p}'");
+                    }
                     if !rq {
                         inner.push('?');
                     }
@@ -130,6 +260,100 @@ impl ApiType {
         }
     }
 
+    /// a union of the literals, with `def` the list of the values and a
+    /// guard for `guard` values are defined next to it
+    fn enum_ts(&self, lits: &[String], guard: &str, def: bool) -> String {
+        let mut out = lits.join("|");
+        if let (true, Some(name)) = (def, &self.name) {
+            out.push_str(";\n");
+            let snake = pascal_to_snake(name);
+            let cname = snake.to_uppercase();
+            out.push_str("export const ");
+            out.push_str(&cname);
+            out.push_str(" = [");
+            for lit in lits {
+                out.push_str(lit);
+                out.push(',');
+            }
+            out.push_str("] as const;\n");
+            let lname = snake.to_lowercase();
+            out += &formatdoc! {"
+            export function is_{lname}(value: {guard}): value is {name} {{
+                return {cname}.includes(value as {name})
+            }}
+            "};
+        }
+
+        out
+    }
+
+    /// the names, literals and descriptions of the members of an enum,
+    /// null is not a member of a nullable enum
+    fn enum_members(&self) -> Option<Vec<(String, ApiLiteral, Option<&str>)>> {
+        let (lits, doc) = match &self.kind {
+            ApiKind::Prim(ApiPrim::Option(at)) => return at.enum_members(),
+            ApiKind::StrEnum(se, doc) => (
+                Vec::from_iter(se.iter().map(|s| ApiLiteral::Str(s.clone()))),
+                doc,
+            ),
+            ApiKind::Enum(lits, doc) => (lits.clone(), doc),
+            _ => return None,
+        };
+        let names = doc.members(lits.iter().map(ApiLiteral::member));
+        Some(Vec::from_iter(
+            names
+                .into_iter()
+                .zip(lits)
+                .enumerate()
+                .map(|(i, (name, lit))| (name, lit, doc.description(i))),
+        ))
+    }
+
+    /// a `const` object with a property for every member of a named enum
+    pub fn enum_object_ts(&self) -> Option<String> {
+        let name = self.name.as_ref()?;
+        let mut out = format!("export const {name} = {{\n");
+        for (member, lit, desc) in self.enum_members()? {
+            out.extend(desc.map(|d| ts_doc(d, "    ")));
+            out += &format!("    {member}: {},\n", lit.ts());
+        }
+        out.push_str("} as const;\n");
+        Some(out)
+    }
+
+    /// a named enum as a typescript `enum` with its list of values and
+    /// guard. only strings and numbers can be in an `enum`
+    pub fn enum_def_ts(&self) -> Option<String> {
+        let name = self.name.as_ref()?;
+        if let ApiKind::Prim(ApiPrim::Option(_)) = self.kind {
+            return None;
+        }
+        let members = self.enum_members()?;
+        if members.iter().any(|(_, l, _)| matches!(l, ApiLiteral::Bool(_))) {
+            return None;
+        }
+
+        let mut out = format!("export enum {name} {{\n");
+        let mut values = String::with_capacity(256);
+        for (member, lit, desc) in members {
+            out.extend(desc.map(|d| ts_doc(d, "    ")));
+            out += &format!("    {member} = {},\n", lit.ts());
+            values += &format!("{name}.{member},");
+        }
+        out.push_str("}\n");
+
+        let snake = pascal_to_snake(name);
+        let cname = snake.to_uppercase();
+        let lname = snake.to_lowercase();
+        out += &formatdoc! {"
+            export const {cname} = [{values}] as const;
+            export function is_{lname}(value: unknown): value is {name} {{
+                return {cname}.includes(value as {name})
+            }}
+        "};
+        Some(out)
+    }
+
     pub fn ref_or_body_ts(&self, for_input: bool) -> String {
         if let Some(n) = &self.name {
             // if !for_input && n == "Gene" {
@@ -144,11 +368,76 @@ impl ApiType {
 
 // dart
 
+/// words that can not name a dart variable, parameter or record field
+pub const DART_RESERVED: &[&str] = &[
+    "assert",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "else",
+    "enum",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "for",
+    "if",
+    "in",
+    "is",
+    "new",
+    "null",
+    "rethrow",
+    "return",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+    // members of every record
+    "hashCode",
+    "runtimeType",
+    "noSuchMethod",
+    "toString",
+];
+
+/// a dart identifier for a property or parameter, e.g. `x_tenant_id` for
+/// `X-Tenant-Id`. record fields and named parameters can not start with `_`
+pub fn dart_ident(name: &str) -> String {
+    let mut var: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if var != name {
+        var = var.to_lowercase();
+    }
+    let mut var = var.trim_start_matches('_').to_string();
+    if var.is_empty() || var.starts_with(|c: char| c.is_ascii_digit()) {
+        var.insert(0, 'v');
+    }
+    if DART_RESERVED.contains(&var.as_str()) {
+        var.push('_');
+    }
+    var
+}
+
 pub fn snake_to_pascal(value: &str) -> String {
     let mut out = String::with_capacity(value.len());
-    for w in value.split('_') {
-        out.push_str(&w[..1].to_uppercase());
-        out.push_str(&w[1..]);
+    for w in value.split('_').filter(|w| !w.is_empty()) {
+        let mut chars = w.chars();
+        out.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+        out.push_str(chars.as_str());
     }
     out
 }
@@ -157,42 +446,20 @@ impl ApiType {
     pub fn def_dart(&self, for_input: bool) -> String {
         let name = self.name.as_ref().unwrap();
         match &self.kind {
-            ApiKind::Unknown => panic!("unknown type: {self:?}"),
+            // lenient mode keeps unsupported schemas as unknown
+            ApiKind::Unknown => Self::dart_dynamic(name),
             ApiKind::Recursive => unreachable!("recursive"),
 
             ApiKind::Prim(p) => {
                 format!("typedef {name} = {};\n", p.dart(for_input))
             }
-            ApiKind::Map(_) => format!("idk yet"),
-            ApiKind::StrEnum(se) => {
-                let mut eel = String::with_capacity(512);
-                let mut een = String::with_capacity(512);
-                for (i, e) in se.iter().enumerate() {
-                    let eup = e.to_uppercase();
-                    eel.push_str("    static const ");
-                    eel.push_str(&format!("{eup} = {name}._('{e}');\n"));
-                    een.push_str(&eup);
-                    if i + 1 != se.len() {
-                        een.push(',');
-                    }
-                }
-
-                formatdoc! {"
-                    class {name} {{
-                        final String value;
-                        const {name}._(this.value);
-                        
-                    {eel}
-                        
-                        static const values = [{een}];
-
-                        factory {name}.fromJson(String json) {{
-                            return values.firstWhere((v) => v.value == json);
-                        }}
-
-                        String toJson() => value;
-                    }}
-                "}
+            ApiKind::Map(v) => format!(
+                "typedef {name} = Map<String, {}>;\n",
+                v.ref_or_body_dart(for_input)
+            ),
+            ApiKind::StrEnum(..) => self.dart_enum(name, "String"),
+            ApiKind::Enum(e, _) => {
+                self.dart_enum(name, ApiLiteral::dart_type(e))
             }
             ApiKind::Array(a) => format!(
                 "typedef {name} = List<{}>;",
@@ -202,133 +469,193 @@ impl ApiType {
 
             ApiKind::Combo(c) => {
                 let mut all = ApiObject::with_capacity(64);
-                let mut uni: Option<&ApiUnion> = None;
+                let mut uni = None;
                 for x in c {
                     match &x.kind {
-                        ApiKind::Union(u) => uni = Some(u),
+                        ApiKind::Union(u) => uni = Self::dart_union_key(u),
+                        ApiKind::Tagged(k, t) => {
+                            uni = Some((
+                                k.to_string(),
+                                t.iter().map(|(t, v)| (t.clone(), v)).collect(),
+                            ))
+                        }
                         ApiKind::Object(o) => all.extend_from_slice(o),
-                        _ => unreachable!(),
+                        _ => return Self::dart_dynamic(name),
                     }
                 }
 
-                if let Some(uni) = uni {
-                    Self::dart_union(name, uni, for_input, &all)
-                } else {
-                    Self::dart_object(name, &all, for_input)
+                match uni {
+                    Some((k, vars)) => {
+                        Self::dart_union(name, &k, &vars, for_input, &all)
+                            .unwrap_or_else(|| Self::dart_dynamic(name))
+                    }
+                    None => Self::dart_object(name, &all, for_input),
                 }
             }
-            ApiKind::Union(uni) => {
-                Self::dart_union(name, uni, for_input, &vec![])
+            ApiKind::Union(uni) => Self::dart_union_key(uni)
+                .and_then(|(k, vars)| {
+                    Self::dart_union(name, &k, &vars, for_input, &vec![])
+                })
+                .unwrap_or_else(|| Self::dart_dynamic(name)),
+            ApiKind::Tagged(k, t) => {
+                let vars =
+                    Vec::from_iter(t.iter().map(|(t, v)| (t.clone(), v)));
+                Self::dart_union(name, k, &vars, for_input, &vec![])
+                    .unwrap_or_else(|| Self::dart_dynamic(name))
             }
             ApiKind::Ref(r) => format!("typedef {name} = {r};\n"),
-            ApiKind::Tuple(tup) => format!("// Tuple: {name} = {tup:?}"),
+            ApiKind::Tuple(_) => {
+                format!("typedef {name} = {};\n", self.dart_body(for_input))
+            }
         }
     }
 
     pub fn ref_or_body_dart(&self, for_input: bool) -> String {
-        if let Some(n) = &self.name {
-            return n.to_string();
+        match &self.name {
+            Some(n) => n.to_string(),
+            None => self.dart_body(for_input),
         }
+    }
 
+    /// the type of an inline schema, unions of inline types have no type
+    /// in dart and are `dynamic`
+    fn dart_body(&self, for_input: bool) -> String {
         match &self.kind {
             ApiKind::Prim(p) => p.dart(for_input),
-            ApiKind::Object(obj) => {
-                let mut inner = String::with_capacity(1024);
-                for (p, v, _rq) in obj {
-                    inner.push_str(&v.ref_or_body_dart(for_input));
-                    inner.push(' ');
-                    inner.push_str(p);
-                    inner.push(',');
-                }
-                format!("({{ {inner} }})")
+            ApiKind::Object(obj) => Self::dart_record(obj, for_input),
+            ApiKind::Combo(ats) if ats.len() == 1 => {
+                ats[0].ref_or_body_dart(for_input)
+            }
+            // the parts of an `allOf` are merged into one record
+            ApiKind::Combo(_) => match Self::dart_objects(self) {
+                Some(objs) => Self::dart_record(
+                    &objs.into_iter().flatten().cloned().collect(),
+                    for_input,
+                ),
+                None => ApiPrim::Any.dart(for_input),
+            },
+            ApiKind::Tuple(tup) => {
+                let items = Vec::from_iter(
+                    tup.iter().map(|at| at.ref_or_body_dart(for_input)),
+                );
+                // a record with one positional field needs a comma
+                let comma = if items.len() == 1 { "," } else { "" };
+                format!("({}{comma})", items.join(", "))
             }
             ApiKind::Array(at) => {
                 format!("List<{}>", at.ref_or_body_dart(for_input))
             }
-            _ => unreachable!("{self:#?}"),
+            ApiKind::Map(v) => {
+                format!("Map<String, {}>", v.ref_or_body_dart(for_input))
+            }
+            ApiKind::StrEnum(..) => ApiPrim::Str.dart(for_input),
+            ApiKind::Enum(e, _) => ApiLiteral::dart_type(e).to_string(),
+            ApiKind::Ref(r) => r.to_string(),
+            ApiKind::Union(_)
+            | ApiKind::Tagged(..)
+            | ApiKind::Recursive
+            | ApiKind::Unknown => ApiPrim::Any.dart(for_input),
         }
     }
 
-    fn dart_union(
-        name: &str, uni: &ApiUnion, for_input: bool, added: &ApiObject,
-    ) -> String {
+    /// a record with a named field for every property
+    fn dart_record(obj: &ApiObject, for_input: bool) -> String {
+        let mut inner = String::with_capacity(1024);
+        for (p, v, _rq) in obj {
+            inner.push_str(&v.ref_or_body_dart(for_input));
+            inner.push(' ');
+            inner.push_str(&dart_ident(p));
+            inner.push(',');
+        }
+        format!("({{ {inner} }})")
+    }
+
+    /// guesses the union key from the single value enum property that
+    /// most of the variants have
+    fn dart_union_key(
+        uni: &ApiUnion,
+    ) -> Option<(String, Vec<(String, &Self)>)> {
         let mut ukeys = IndexMap::<String, usize>::with_capacity(2);
-        // let mut init = String::with_capacity(1024);
-        // let mut from_json = String::with_capacity(1024);
-        // let mut into_json = String::with_capacity(1024);
-
-        let mut find_uk = |obj: &ApiObject| {
-            for (n, v, _rq) in obj {
-                if let ApiKind::StrEnum(se) = &v.kind {
-                    if se.len() == 1 {
-                        if let Some(uk) = ukeys.get_mut(n) {
-                            *uk += 1;
-                        } else {
-                            ukeys.insert(n.to_string(), 1);
+        for u in uni {
+            for obj in Self::dart_objects(u)? {
+                for (n, v, _rq) in obj {
+                    if let ApiKind::StrEnum(se, _) = &v.kind {
+                        if se.len() == 1 {
+                            *ukeys.entry(n.to_string()).or_default() += 1;
                         }
                     }
                 }
             }
-        };
+        }
 
+        let (uk, _) = ukeys.into_iter().max_by_key(|(_, t)| *t)?;
+        let mut variants = Vec::with_capacity(uni.len());
         for u in uni {
-            match &u.kind {
-                ApiKind::Object(obj) => find_uk(obj),
-                ApiKind::Combo(c) => {
-                    for a in c {
-                        if let ApiKind::Object(obj) = &a.kind {
-                            find_uk(obj);
-                            continue;
-                        }
-                        unreachable!()
+            let tag = Self::dart_objects(u)?.into_iter().flatten().find_map(
+                |(k, v, _)| match &v.kind {
+                    ApiKind::StrEnum(se, _) if *k == uk && se.len() == 1 => {
+                        Some(se[0].clone())
                     }
-                }
-                _ => unreachable!(),
-            }
+                    _ => None,
+                },
+            )?;
+            variants.push((tag, u));
         }
 
-        let (uk, _) = ukeys.iter().max_by_key(|(_, t)| **t).unwrap();
+        Some((uk, variants))
+    }
+
+    /// the objects a union variant is made of
+    fn dart_objects(at: &Self) -> Option<Vec<&ApiObject>> {
+        match &at.kind {
+            ApiKind::Object(o) => Some(vec![o]),
+            ApiKind::Combo(c) => c
+                .iter()
+                .map(|a| match &a.kind {
+                    ApiKind::Object(o) => Some(o),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
 
+    /// `key` is the property that holds the tag of each variant
+    fn dart_union(
+        name: &str, key: &str, variants: &[(String, &Self)], for_input: bool,
+        added: &ApiObject,
+    ) -> Option<String> {
         let mut vars = String::with_capacity(4096);
 
-        for u in uni {
+        for (tag, u) in variants {
             let mut inner = String::with_capacity(512);
-            let mut var_key = String::new();
+            let mut var_key = snake_to_pascal(
+                &tag.replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+            );
+            if var_key.is_empty()
+                || var_key.starts_with(|c: char| c.is_ascii_digit())
+            {
+                var_key.insert(0, 'V');
+            }
 
-            let mut dooj = |obj: &ApiObject| {
+            for obj in [added].into_iter().chain(Self::dart_objects(u)?) {
                 for (k, v, _rq) in obj {
-                    if k == uk {
-                        let ApiKind::StrEnum(se) = &v.kind else {
-                            unreachable!()
-                        };
-                        var_key = snake_to_pascal(&se[0]);
+                    if k == key {
                         continue;
                     }
 
+                    let ident = dart_ident(k);
+                    if ident != *k {
+                        inner += &format!("@JsonKey(name: '{k}') ");
+                    }
                     inner += &format!(
-                        "required {} {k},\n",
+                        "required {} {ident},\n",
                         v.ref_or_body_dart(for_input)
                     );
                 }
-            };
-
-            dooj(added);
-
-            match &u.kind {
-                ApiKind::Object(o) => {
-                    dooj(o);
-                }
-                ApiKind::Combo(c) => {
-                    for at in c {
-                        let ApiKind::Object(o) = &at.kind else {
-                            unreachable!()
-                        };
-                        dooj(o);
-                    }
-                }
-                _ => panic!("union is not object or combo"),
             }
 
+            vars += &format!("@FreezedUnionValue('{tag}')\n");
             if inner.is_empty() {
                 vars += &format!(
                     "const factory {name}.{var_key}() = {name}{var_key};\n"
@@ -338,22 +665,78 @@ impl ApiType {
             }
         }
 
-        formatdoc! {"
-            @Freezed(unionKey: '{uk}', unionValueCase: FreezedUnionCase.snake)
+        Some(formatdoc! {"
+            @Freezed(unionKey: '{key}')
             class {name} with _${name} {{
                 {vars}
 
                 factory {name}.fromJson(JsonObject json) => _${name}FromJson(json);
             }}
+        "})
+    }
+
+    /// dart has no unions of primitives or inline types
+    /// a class with a constant for every member of an enum
+    fn dart_enum(&self, name: &str, ty: &str) -> String {
+        let members = self.enum_members().unwrap_or_default();
+        let mut eel = String::with_capacity(512);
+        let mut een = Vec::with_capacity(members.len());
+        // members can not be dart keywords or the members of the class
+        let taken = ["value", "values", "fromJson", "toJson"];
+        let mut names = Vec::<String>::with_capacity(members.len());
+        for (member, ..) in members.iter() {
+            let mut name = member.clone();
+            while DART_RESERVED.contains(&name.as_str())
+                || taken.contains(&name.as_str())
+                || names.contains(&name)
+                || (name != *member && members.iter().any(|m| m.0 == name))
+            {
+                name.push('_');
+            }
+            names.push(name);
+        }
+        for ((_, lit, desc), member) in members.iter().zip(&names) {
+            for line in desc.iter().flat_map(|d| d.lines()) {
+                eel.push_str(&format!("    /// {line}\n"));
+            }
+            eel.push_str("    static const ");
+            eel.push_str(&format!("{member} = {name}._({});\n", lit.dart()));
+            een.push(member.as_str());
+        }
+        let een = een.join(",");
+
+        formatdoc! {"
+            class {name} {{
+                final {ty} value;
+                const {name}._(this.value);
+                
+            {eel}
+                
+                static const values = [{een}];
+
+                factory {name}.fromJson({ty} json) {{
+                    return values.firstWhere((v) => v.value == json);
+                }}
+
+                {ty} toJson() => value;
+            }}
         "}
     }
 
+    fn dart_dynamic(name: &str) -> String {
+        format!("typedef {name} = dynamic;\n")
+    }
+
     fn dart_object(name: &str, object: &ApiObject, for_input: bool) -> String {
         let gg = object.iter().any(|(k, _t, _rq)| k.starts_with('_'));
         if !gg {
             let mut inner = String::with_capacity(2048);
             for (p, v, _rq) in object {
-                inner += &format!("{} {p},\n", v.ref_or_body_dart(for_input));
+                inner += &format!(
+                    "{} {},\n",
+                    v.ref_or_body_dart(for_input),
+                    dart_ident(p)
+                );
             }
             return format!("typedef {name} = ({{{inner}}});");
         }
@@ -364,7 +747,7 @@ impl ApiType {
         let mut into_json = String::with_capacity(1024);
 
         for (p, v, _rq) in object {
-            let pn = p.strip_prefix("_").unwrap_or(p);
+            let pn = dart_ident(p);
             props +=
                 &format!("final {} {pn};\n", v.ref_or_body_dart(for_input));
             init += &format!("required this.{pn},");
@@ -388,3 +771,99 @@ impl ApiType {
         "}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pascal_case() {
+        assert_eq!(snake_to_pascal("in_progress"), "InProgress");
+        assert_eq!(snake_to_pascal("__a__b_"), "AB");
+        assert_eq!(snake_to_pascal("étage"), "étage");
+        assert_eq!(snake_to_pascal(""), "");
+    }
+
+    #[test]
+    fn enum_idents() {
+        assert_eq!(enum_ident("IN-PROGRESS"), "IN_PROGRESS");
+        assert_eq!(enum_ident("2FA"), "V2FA");
+        assert_eq!(enum_ident(""), "V");
+        assert_eq!(enum_ident("ÉTAGE"), "_TAGE");
+        assert_eq!(ApiLiteral::Int(-2).member(), "V_2");
+        assert_eq!(ApiLiteral::Float(1.5).member(), "V1_5");
+        assert_eq!(ApiLiteral::Bool(true).member(), "TRUE");
+    }
+
+    #[test]
+    fn enum_members_are_unique() {
+        let values = || ["A", "A", "B", "A_2"].map(String::from).into_iter();
+        let doc = ApiEnumDoc::default();
+        assert_eq!(doc.members(values()), ["A", "A_2", "B", "A_2_2"]);
+
+        // varnames win over the values and are made into identifiers
+        let doc = ApiEnumDoc {
+            varnames: ["in-progress", "done", "done"].map(String::from).into(),
+            descriptions: vec!["".to_string(), "finished".to_string()],
+        };
+        assert_eq!(doc.members(values()), ["in_progress", "done", "done_2"]);
+        assert_eq!(doc.description(0), None);
+        assert_eq!(doc.description(1), Some("finished"));
+        assert_eq!(doc.description(2), None);
+    }
+
+    #[test]
+    fn dart_enum_members_are_escaped() {
+        let mode = ApiType::new(
+            Some("Mode".to_string()),
+            ApiKind::StrEnum(
+                ["a", "b", "c", "d"].map(String::from).into(),
+                ApiEnumDoc {
+                    varnames: ["default", "values", "value", "default_"]
+                        .map(String::from)
+                        .into(),
+                    descriptions: vec![],
+                },
+            ),
+        );
+        let dart = mode.def_dart(false);
+        for member in [
+            "static const default__ = Mode._('a');",
+            "static const values_ = Mode._('b');",
+            "static const value_ = Mode._('c');",
+            "static const default_ = Mode._('d');",
+            "static const values = [default__,values_,value_,default_];",
+        ] {
+            assert!(dart.contains(member), "{member} is not in\n{dart}");
+        }
+    }
+
+    #[test]
+    fn dart_union_of_non_ascii_tags() {
+        let object = |name: &str| {
+            let kind = ApiKind::Object(vec![(
+                "n".to_string(),
+                ApiType::new(None, ApiKind::Prim(ApiPrim::Int)),
+                true,
+            )]);
+            ApiType::new(Some(name.to_string()), kind)
+        };
+        let floor = ApiType::new(
+            Some("Floor".to_string()),
+            ApiKind::Tagged(
+                "kind".to_string(),
+                vec![
+                    ("étage".to_string(), object("Etage")),
+                    ("2nd".to_string(), object("Second")),
+                    ("ü".to_string(), object("U")),
+                ],
+            ),
+        );
+
+        let dart = floor.def_dart(false);
+        assert!(dart.contains("@FreezedUnionValue('étage')"));
+        assert!(dart.contains("const factory Floor.Tage("));
+        assert!(dart.contains("const factory Floor.V2nd("));
+        assert!(dart.contains("const factory Floor.V("));
+    }
+}
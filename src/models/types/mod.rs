@@ -1,19 +1,56 @@
 mod def;
 mod parse;
+mod revive;
+
+pub use def::dart_ident;
+pub use revive::Revive;
 
 #[derive(Debug, Clone)]
 pub enum ApiPrim {
     Str,
+    /// `format: date-time`
+    DateTime,
+    /// `format: date`
+    Date,
+    /// `format: uuid`
+    Uuid,
+    /// base64 encoded bytes, `format: byte`
+    Byte,
     Int,
+    /// `format: int64` integers that do not fit in a double
+    Int64,
     Float,
     Bool,
     File,
     Null,
+    /// any value, used for schemas that could not be modeled
+    Any,
+    /// a format that the config maps to a type, holds its alias
+    Format(String),
     Option(Box<ApiType>),
 }
 
+/// a value of an `enum` or `const`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiLiteral {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+/// names and descriptions of the members of an enum, from
+/// `x-enum-varnames` and `x-enum-descriptions`. empty when not given
+#[derive(Debug, Clone, Default)]
+pub struct ApiEnumDoc {
+    pub varnames: Vec<String>,
+    pub descriptions: Vec<String>,
+}
+
 pub type ApiObject = Vec<(String, ApiType, bool)>;
 pub type ApiUnion = Vec<ApiType>;
+/// the value of the discriminator property and the variant
+pub type ApiTagged = Vec<(String, ApiType)>;
 
 #[derive(Debug, Clone)]
 pub enum ApiKind {
@@ -36,6 +73,11 @@ pub enum ApiKind {
     /// type Union = number | string
     /// ```
     Union(ApiUnion),
+    /// a union with a discriminator property, for example in `typescript`:
+    /// ```typescript
+    /// type Tagged = ({ kind: "cat" } & Cat) | ({ kind: "dog" } & Dog)
+    /// ```
+    Tagged(String, ApiTagged),
     /// for example in `typescript`:
     /// ```typescript
     /// type Combo = { a: string } & { b: number }
@@ -45,7 +87,12 @@ pub enum ApiKind {
     /// ```typescript
     /// type StrEnum = 'a' | 'b' | 'c'
     /// ```
-    StrEnum(Vec<String>),
+    StrEnum(Vec<String>, ApiEnumDoc),
+    /// an enum of other or mixed literals, for example in `typescript`:
+    /// ```typescript
+    /// type Enum = 1 | 2 | true | "a"
+    /// ```
+    Enum(Vec<ApiLiteral>, ApiEnumDoc),
     /// for example in `typescript`:
     /// ```typescript
     /// type Tuple = [number, string, { a: boolean }]
@@ -1,93 +1,90 @@
 use super::*;
+use crate::config::FormatType;
+use crate::diag::ptr;
+use crate::models::Ctx;
 use crate::openapi::{
     array::ArrayItems,
-    common::{Def, OaSchema, RefOr, SchemaType, Type},
+    common::{Def, Discriminator, OaSchema, Ref, RefOr, SchemaType, Type},
     format::{KnownFormat, SchemaFormat},
-    object::AdditionalProperties,
+    object::{AdditionalProperties, Object},
 };
-use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
 use std::collections::HashSet;
 
 impl ApiType {
+    /// `loc` is the json pointer of `value` inside the spec
     pub fn parse_openapi(
-        name: Option<String>, value: &RefOr<OaSchema>,
-        mut parents: HashSet<String>, types: &mut IndexMap<String, ApiType>,
-        schemas: &IndexMap<String, RefOr<OaSchema>>,
+        name: Option<String>, value: &RefOr<OaSchema>, loc: &str,
+        parents: HashSet<String>, ctx: &mut Ctx,
     ) -> Self {
         let mut aty = Self::new(name.clone(), ApiKind::Unknown);
 
         let schema = match value {
             RefOr::T(s) => s,
-            RefOr::Ref(r) => {
-                let i = r.loc.split('/').last().unwrap();
-                if let Some(aty) = types.get(i) {
-                    return aty.clone();
-                }
-                if parents.contains(i) {
-                    return ApiType::new(
-                        Some(i.to_string()),
-                        ApiKind::Recursive,
-                    );
-                }
-                let Some(s) = schemas.get(i) else {
-                    panic!("ref not found: {i}");
-                };
-                parents.insert(i.to_string());
-                let t = ApiType::parse_openapi(
-                    Some(i.to_string()),
-                    s,
-                    parents,
-                    types,
-                    schemas,
-                );
-                types.insert(i.to_string(), t.clone());
-                return t;
-            }
+            RefOr::Ref(r) => return Self::parse_ref(r, loc, parents, ctx),
         };
 
-        assert!(!schema.is_user_defined());
+        if schema.is_user_defined() {
+            ctx.diags.error(loc, "#user_defined schemas are not supported");
+            return aty;
+        }
 
         match schema {
             OaSchema::Object(o) => {
-                let mut nullable = false;
-                let oty = match &o.schema_type {
-                    SchemaType::AnyValue => panic!("any: {o:?}"),
-                    SchemaType::Array(a) => {
-                        assert_eq!(a.len(), 2);
-                        nullable = true;
-                        assert!(a[1] == Type::Null);
-                        &a[0]
-                    }
-                    SchemaType::Type(t) => t,
-                };
+                if let Some(kind) = Self::parse_enum(o, loc, ctx) {
+                    aty.kind = kind;
+                    return aty;
+                }
+
+                let (oty, nullable) =
+                    match Self::schema_type(&o.schema_type, loc, ctx) {
+                        Ok(v) => v,
+                        Err(kind) => {
+                            aty.kind = kind;
+                            return aty;
+                        }
+                    };
 
                 let kind = match oty {
                     Type::Object => 'k: {
                         if let Some(ap) = &o.additional_properties {
+                            let aploc = ptr(loc, "additionalProperties");
                             let r = match &**ap {
-                                AdditionalProperties::RefOr(r) => r,
-                                AdditionalProperties::FreeForm(_) => {
-                                    panic!("free form map")
+                                AdditionalProperties::RefOr(r) => Some(r),
+                                AdditionalProperties::FreeForm(false) => None,
+                                AdditionalProperties::FreeForm(true) => {
+                                    let kind = ctx.unsupported(
+                                        &aploc,
+                                        "free form maps are not supported",
+                                        ApiPrim::Any.into(),
+                                    );
+                                    break 'k ApiKind::Map(Box::new(
+                                        ApiType::new(None, kind),
+                                    ));
                                 }
                             };
-                            break 'k ApiKind::Map(Box::new(
-                                ApiType::parse_openapi(
-                                    None, r, parents, types, schemas,
-                                ),
-                            ));
+                            if let Some(r) = r {
+                                break 'k ApiKind::Map(Box::new(
+                                    ApiType::parse_openapi(
+                                        None, r, &aploc, parents, ctx,
+                                    ),
+                                ));
+                            }
                         }
 
                         let cap = o.properties.len();
                         let mut obj = Vec::with_capacity(cap);
+                        let props = ptr(loc, "properties");
                         for (kp, vp) in o.properties.iter() {
                             obj.push((
                                 kp.to_string(),
                                 ApiType::parse_openapi(
                                     None,
                                     vp,
+                                    &ptr(&props, kp),
                                     parents.clone(),
-                                    types,
-                                    schemas,
+                                    ctx,
                                 ),
                                 o.required.contains(kp),
                             ));
@@ -95,32 +92,62 @@ impl ApiType {
                         ApiKind::Object(obj)
                     }
                     Type::String => 'str: {
-                        if let Some(ev) = &o.enum_values {
-                            let x = ev
-                                .iter()
-                                .map(|v| v.as_str().unwrap().to_string())
-                                .collect();
-                            break 'str ApiKind::StrEnum(x);
+                        let prim = match &o.format {
+                            Some(SchemaFormat::KnownFormat(kf)) => match kf {
+                                KnownFormat::Binary => ApiPrim::File,
+                                KnownFormat::DateTime => ApiPrim::DateTime,
+                                KnownFormat::Date => ApiPrim::Date,
+                                KnownFormat::Uuid => ApiPrim::Uuid,
+                                KnownFormat::Byte => ApiPrim::Byte,
+                                // formats such as email and uri are plain
+                                // strings
+                                _ => ApiPrim::Str,
+                            },
+                            _ => ApiPrim::Str,
+                        };
+
+                        if let Some(alias) =
+                            ctx.format(FormatType::String, &o.format, &prim)
+                        {
+                            break 'str ApiPrim::Format(alias).into();
                         }
 
-                        if let Some(fmt) = &o.format {
-                            if let SchemaFormat::KnownFormat(kf) = fmt {
-                                if matches!(kf, KnownFormat::Binary) {
-                                    break 'str ApiPrim::File.into();
-                                }
-                                panic!("unknown str format: {kf:?}");
-                            } else {
-                                panic!("custom format: {o:#?}");
-                            }
+                        if let Some(SchemaFormat::Custom(cf)) = &o.format {
+                            // the value is still a string
+                            break 'str ctx.unsupported(
+                                &ptr(loc, "format"),
+                                format!("custom format: {cf}"),
+                                ApiPrim::Str.into(),
+                            );
                         }
 
-                        ApiPrim::Str.into()
+                        prim.into()
                     }
                     Type::Null => ApiPrim::Null.into(),
                     Type::Boolean => ApiPrim::Bool.into(),
-                    Type::Integer => ApiPrim::Int.into(),
-                    Type::Number => ApiPrim::Float.into(),
-                    _ => panic!("unknown oty: {oty:?}"),
+                    Type::Integer => {
+                        let prim = match &o.format {
+                            Some(SchemaFormat::KnownFormat(
+                                KnownFormat::Int64 | KnownFormat::UInt64,
+                            )) => ApiPrim::Int64,
+                            _ => ApiPrim::Int,
+                        };
+                        ctx.format(FormatType::Integer, &o.format, &prim)
+                            .map_or(prim, ApiPrim::Format)
+                            .into()
+                    }
+                    Type::Number => ctx
+                        .format(FormatType::Number, &o.format, &ApiPrim::Float)
+                        .map_or(ApiPrim::Float, ApiPrim::Format)
+                        .into(),
+                    Type::Array => ctx.unsupported(
+                        &ptr(loc, "type"),
+                        "array schema without items",
+                        ApiKind::Array(Box::new(ApiType::new(
+                            None,
+                            ApiPrim::Any.into(),
+                        ))),
+                    ),
                 };
 
                 if nullable {
@@ -136,87 +163,74 @@ impl ApiType {
                 // o.content_media_type
             }
             OaSchema::AllOf(af) => {
+                let items = ptr(loc, "allOf");
                 aty.kind = ApiKind::Combo(
                     af.items
                         .iter()
-                        .map(|v| {
+                        .enumerate()
+                        .map(|(i, v)| {
                             ApiType::parse_openapi(
                                 None,
                                 v,
+                                &ptr(&items, i),
                                 parents.clone(),
-                                types,
-                                schemas,
+                                ctx,
                             )
                         })
                         .collect(),
                 );
             }
             OaSchema::OneOf(of) => {
-                let mut uni = Vec::with_capacity(of.items.len());
-                for v in &of.items {
-                    uni.push(ApiType::parse_openapi(
-                        None,
-                        v,
-                        parents.clone(),
-                        types,
-                        schemas,
-                    ));
-                }
-
-                if uni.len() == 1 {
-                    return uni[0].clone();
-                    // panic!("wtf: {uni:#?}");
-                }
-
-                let mut updated = false;
-                if uni.len() == 2 {
-                    let mut other: Option<ApiType> = None;
-                    let mut is_option = false;
-                    for x in &uni {
-                        if x.is_null() {
-                            is_option = true
-                        } else {
-                            other = Some(x.clone());
-                        }
-                    }
-
-                    if other.is_some() && is_option {
-                        aty.kind = ApiKind::Prim(ApiPrim::Option(Box::new(
-                            other.unwrap(),
-                        )));
-                        updated = true;
-                    }
-                }
-
-                if !updated {
-                    aty.kind = ApiKind::Union(uni);
-                }
+                return Self::parse_union(
+                    aty,
+                    &of.items,
+                    of.discriminator.as_ref(),
+                    &ptr(loc, "oneOf"),
+                    parents,
+                    ctx,
+                );
+            }
+            OaSchema::AnyOf(af) => {
+                return Self::parse_union(
+                    aty,
+                    &af.items,
+                    af.discriminator.as_ref(),
+                    &ptr(loc, "anyOf"),
+                    parents,
+                    ctx,
+                );
             }
             OaSchema::Array(a) => {
-                let mut nullable = false;
-                let _ = match &a.schema_type {
-                    SchemaType::AnyValue => panic!("any: {a:?}"),
-                    SchemaType::Array(a) => {
-                        assert_eq!(a.len(), 2);
-                        nullable = true;
-                        assert!(a[1] == Type::Null);
-                        &a[0]
-                    }
-                    SchemaType::Type(t) => t,
-                };
+                let (_, nullable) =
+                    match Self::schema_type(&a.schema_type, loc, ctx) {
+                        Ok(v) => v,
+                        Err(kind) => {
+                            aty.kind = kind;
+                            return aty;
+                        }
+                    };
 
                 let ArrayItems::R(item) = &a.items else {
-                    assert!(!a.prefix_items.is_empty());
+                    if a.prefix_items.is_empty() {
+                        aty.kind = ctx.unsupported(
+                            &ptr(loc, "prefixItems"),
+                            "tuple without prefixItems",
+                            ApiPrim::Any.into(),
+                        );
+                        return aty;
+                    }
+                    let items = ptr(loc, "prefixItems");
                     aty.kind = ApiKind::Tuple(
                         a.prefix_items
                             .iter()
-                            .map(|v| {
+                            .enumerate()
+                            .map(|(i, v)| {
                                 ApiType::parse_openapi(
                                     None,
                                     &RefOr::T(v.clone()),
+                                    &ptr(&items, i),
                                     parents.clone(),
-                                    types,
-                                    schemas,
+                                    ctx,
                                 )
                             })
                             .collect(),
@@ -225,11 +239,17 @@ impl ApiType {
                     return aty;
                 };
 
-                let item =
-                    ApiType::parse_openapi(None, item, parents, types, schemas);
+                let item = ApiType::parse_openapi(
+                    None,
+                    item,
+                    &ptr(loc, "items"),
+                    parents,
+                    ctx,
+                );
 
-                if a.max_items == a.min_items && a.max_items.is_some() {
-                    let len = a.max_items.unwrap();
+                if let (Some(len), true) =
+                    (a.max_items, a.max_items == a.min_items)
+                {
                     // println!("len: {len}");
                     aty.kind = ApiKind::Tuple(vec![item; len]);
                     // return aty;
@@ -249,13 +269,286 @@ impl ApiType {
                 // a.max_items;
                 // a.min_items;
             }
-            s => panic!("unknown: {s:#?}"),
         };
 
-        if matches!(aty.kind, ApiKind::Unknown) {
-            panic!("returing unknown: {value:#?}");
+        aty
+    }
+
+    /// `enum` and `const` of literals, a null value makes it an option.
+    /// enums of strings only are a [`ApiKind::StrEnum`]
+    fn parse_enum(o: &Object, loc: &str, ctx: &mut Ctx) -> Option<ApiKind> {
+        let (values, vloc) = match (&o.enum_values, &o.const_value) {
+            (Some(ev), _) => (ev.as_slice(), ptr(loc, "enum")),
+            (None, Some(cv)) => (std::slice::from_ref(cv), ptr(loc, "const")),
+            (None, None) => return None,
+        };
+        // enums of objects and arrays are checked as their type
+        if values.iter().any(|v| v.is_object() || v.is_array()) {
+            return None;
+        }
+
+        // the extensions have an item for every value, null included
+        let mut ext = |key: &str, list: &Option<Vec<String>>| {
+            let Some(list) = list else { return Vec::new() };
+            if list.len() != values.len() {
+                ctx.diags.warn(
+                    &ptr(loc, key),
+                    format!(
+                        "{key} has {} items for {} values",
+                        list.len(),
+                        values.len()
+                    ),
+                );
+                return Vec::new();
+            }
+            Vec::from_iter(
+                list.iter()
+                    .zip(values)
+                    .filter(|(_, v)| !v.is_null())
+                    .map(|(item, _)| item.clone()),
+            )
+        };
+        let doc = ApiEnumDoc {
+            varnames: ext("x-enum-varnames", &o.enum_varnames),
+            descriptions: ext("x-enum-descriptions", &o.enum_descriptions),
+        };
+
+        let mut nullable = matches!(
+            &o.schema_type,
+            SchemaType::Array(a) if a.contains(&Type::Null)
+        );
+        let mut lits = Vec::with_capacity(values.len());
+        for v in values {
+            lits.push(match v {
+                Value::String(s) => ApiLiteral::Str(s.clone()),
+                Value::Bool(b) => ApiLiteral::Bool(*b),
+                Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+                    (Some(i), _) => ApiLiteral::Int(i),
+                    (None, Some(f)) => ApiLiteral::Float(f),
+                    (None, None) => {
+                        return Some(ctx.unsupported(
+                            &vloc,
+                            format!("enum value out of range: {n}"),
+                            ApiPrim::Any.into(),
+                        ))
+                    }
+                },
+                Value::Null => {
+                    nullable = true;
+                    continue;
+                }
+                _ => unreachable!("checked above"),
+            });
+        }
+
+        let strs = Vec::from_iter(lits.iter().map_while(|l| match l {
+            ApiLiteral::Str(s) => Some(s.clone()),
+            _ => None,
+        }));
+        let kind = match lits.len() {
+            0 => return Some(ApiPrim::Null.into()),
+            len if strs.len() == len => ApiKind::StrEnum(strs, doc),
+            _ => ApiKind::Enum(lits, doc),
+        };
+        if nullable {
+            return Some(
+                ApiPrim::Option(Box::new(ApiType::new(None, kind))).into(),
+            );
+        }
+        Some(kind)
+    }
+
+    /// `oneOf` and `anyOf`, a union of a type and null is an option.
+    /// `loc` is the location of the list of `items`
+    fn parse_union(
+        mut aty: Self, items: &[RefOr<OaSchema>],
+        discriminator: Option<&Discriminator>, loc: &str,
+        parents: HashSet<String>, ctx: &mut Ctx,
+    ) -> Self {
+        // no type can be made of no items
+        if items.is_empty() {
+            aty.kind = ctx.unsupported(loc, "empty union", ApiPrim::Any.into());
+            return aty;
+        }
+
+        let mut uni = Vec::with_capacity(items.len());
+        for (i, v) in items.iter().enumerate() {
+            uni.push(ApiType::parse_openapi(
+                None,
+                v,
+                &ptr(loc, i),
+                parents.clone(),
+                ctx,
+            ));
+        }
+
+        if let Some(d) = discriminator {
+            let mut tagged = ApiTagged::with_capacity(uni.len());
+            for (i, (item, at)) in items.iter().zip(uni.iter()).enumerate() {
+                let Some(tag) = Self::tag_value(d, item, at) else {
+                    aty.kind = ctx.unsupported(
+                        &ptr(loc, i),
+                        format!("no {} value for the variant", d.property_name),
+                        ApiKind::Union(uni),
+                    );
+                    return aty;
+                };
+                tagged.push((tag, at.clone()));
+            }
+            aty.kind = ApiKind::Tagged(d.property_name.clone(), tagged);
+            return aty;
+        }
+
+        if uni.len() == 1 {
+            return uni[0].clone();
+        }
+
+        let mut updated = false;
+        if uni.len() == 2 {
+            let mut other: Option<ApiType> = None;
+            let mut is_option = false;
+            for x in &uni {
+                if x.is_null() {
+                    is_option = true
+                } else {
+                    other = Some(x.clone());
+                }
+            }
+
+            if let (Some(other), true) = (other, is_option) {
+                aty.kind = ApiKind::Prim(ApiPrim::Option(Box::new(other)));
+                updated = true;
+            }
         }
 
+        if !updated {
+            aty.kind = ApiKind::Union(uni);
+        }
         aty
     }
+
+    /// the discriminator value of a variant, from the mapping or the name of
+    /// the schema. inline variants need a single value enum property
+    fn tag_value(
+        d: &Discriminator, item: &RefOr<OaSchema>, at: &ApiType,
+    ) -> Option<String> {
+        let RefOr::Ref(r) = item else {
+            let ApiKind::Object(obj) = &at.kind else { return None };
+            return obj.iter().find_map(|(k, v, _)| match &v.kind {
+                ApiKind::StrEnum(se, _)
+                    if *k == d.property_name && se.len() == 1 =>
+                {
+                    Some(se[0].clone())
+                }
+                _ => None,
+            });
+        };
+
+        let name = at.name.as_deref();
+        let mapped = d
+            .mapping
+            .iter()
+            .find(|(_, v)| **v == r.loc || Some(v.as_str()) == name);
+        match mapped {
+            Some((tag, _)) => Some(tag.clone()),
+            None => name.map(str::to_string),
+        }
+    }
+
+    /// `loc` is where the ref was found, types are identified by the
+    /// location of their schema so refs from other files resolve the same
+    fn parse_ref(
+        r: &Ref, loc: &str, mut parents: HashSet<String>, ctx: &mut Ctx,
+    ) -> Self {
+        let unknown = Self::new(None, ApiKind::Unknown);
+        let (doc, pointer) = match ctx.docs.locate(ctx.base, &r.loc) {
+            Ok(v) => v,
+            Err(e) => {
+                ctx.diags.error(loc, format!("{}: {e}", r.loc));
+                return unknown;
+            }
+        };
+        let key = ctx.docs.loc(doc, &pointer);
+        let name = ctx.docs.ref_name(doc, &pointer);
+        // names are compared by the schema a chain of refs ends at
+        let (tdoc, tpointer) = ctx.docs.target(doc, &pointer);
+        let target = ctx.docs.loc(tdoc, &tpointer);
+
+        if let Some(name) = &name {
+            match ctx.names.get(name) {
+                Some(k) if *k != target => {
+                    ctx.diags.error(
+                        loc,
+                        format!("{} has the same name as {k}", r.loc),
+                    );
+                    return unknown;
+                }
+                _ => {}
+            }
+            if let Some(aty) = ctx.types.get(name) {
+                return aty.clone();
+            }
+        }
+
+        if parents.contains(&key) {
+            if name.is_none() {
+                ctx.diags.error(
+                    loc,
+                    format!("recursive ref to an unnamed schema: {}", r.loc),
+                );
+            }
+            return ApiType::new(name, ApiKind::Recursive);
+        }
+
+        let Some(value) = ctx.docs.get(doc, &pointer) else {
+            ctx.diags.error(loc, format!("ref not found: {}", r.loc));
+            return unknown;
+        };
+        let schema = match RefOr::<OaSchema>::deserialize(value) {
+            Ok(s) => s,
+            Err(e) => {
+                ctx.diags.error(&key, e);
+                return unknown;
+            }
+        };
+
+        parents.insert(key.clone());
+        if let Some(name) = &name {
+            ctx.names.insert(name.to_string(), target);
+        }
+
+        let t = ctx.with_base(doc, |ctx| {
+            ApiType::parse_openapi(name.clone(), &schema, &key, parents, ctx)
+        });
+
+        if let Some(name) = name {
+            ctx.types.insert(name, t.clone());
+        }
+        t
+    }
+
+    /// the type of the schema and if its nullable. e.g. `["string", "null"]`
+    fn schema_type<'a>(
+        st: &'a SchemaType, loc: &str, ctx: &mut Ctx,
+    ) -> Result<(&'a Type, bool), ApiKind> {
+        match st {
+            SchemaType::AnyValue => Err(ctx.unsupported(
+                &ptr(loc, "type"),
+                "schema without a type",
+                ApiPrim::Any.into(),
+            )),
+            SchemaType::Array(a) => {
+                let mut types = a.iter().filter(|t| **t != Type::Null);
+                match (types.next(), types.next()) {
+                    (Some(t), None) => Ok((t, a.len() > 1)),
+                    _ => Err(ctx.unsupported(
+                        &ptr(loc, "type"),
+                        format!("unsupported type list: {a:?}"),
+                        ApiPrim::Any.into(),
+                    )),
+                }
+            }
+            SchemaType::Type(t) => Ok((t, false)),
+        }
+    }
 }
@@ -0,0 +1,407 @@
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+
+use crate::config::{ByteType, DateType, Int64Type, Types};
+
+use super::{ApiKind, ApiPrim, ApiType};
+
+/// turns parsed json into the typescript types picked in [`Types`], e.g.
+/// `date-time` strings into a `Date`, and back when a request is sent.
+/// every expression converts a value named `v`
+pub struct Revive<'a> {
+    pub types: &'a Types,
+    /// named types get a `_revive_{name}` and `_dump_{name}` function
+    pub named: &'a IndexMap<String, ApiType>,
+}
+
+impl Revive<'_> {
+    fn prim(&self, prim: &ApiPrim, dump: bool) -> Option<&'static str> {
+        let date = |d: DateType| d == DateType::Date;
+        let t = self.types;
+        Some(match (prim, dump) {
+            (ApiPrim::DateTime, false) if date(t.date_time) => "new Date(v)",
+            (ApiPrim::DateTime, true) if date(t.date_time) => {
+                "v.toISOString()"
+            }
+            (ApiPrim::Date, false) if date(t.date) => "new Date(v)",
+            (ApiPrim::Date, true) if date(t.date) => {
+                "v.toISOString().slice(0, 10)"
+            }
+            // a bigint is written by the `JSON.stringify` replacer, values
+            // above 2^53 already are a bigint from `_int64`
+            (ApiPrim::Int64, false) if t.int64.is_bigint() => "BigInt(v)",
+            // a string keeps the digits of the bigint from `_int64`
+            (ApiPrim::Int64, false) if t.int64 == Int64Type::String => {
+                "String(v)"
+            }
+            (ApiPrim::Int64, true) if t.int64 == Int64Type::String => {
+                "BigInt(v)"
+            }
+            (ApiPrim::Byte, false) if t.byte == ByteType::Uint8array => {
+                "Uint8Array.from(atob(v), (c) => c.charCodeAt(0))"
+            }
+            (ApiPrim::Byte, true) if t.byte == ByteType::Uint8array => {
+                "btoa(Array.from(v as Uint8Array, (c) => String.fromCharCode(c)).join(''))"
+            }
+            _ => return None,
+        })
+    }
+
+    /// whether any value inside of `at` is converted
+    pub fn changes(&self, at: &ApiType, dump: bool) -> bool {
+        self.changes_in(at, dump, &mut HashSet::new())
+    }
+
+    fn changes_in(
+        &self, at: &ApiType, dump: bool, seen: &mut HashSet<String>,
+    ) -> bool {
+        if let Some(name) = &at.name {
+            if let ApiKind::Recursive = at.kind {
+                return self
+                    .named
+                    .get(name)
+                    .is_some_and(|at| self.changes_in(at, dump, seen));
+            }
+            // a type that is already being checked is answered by the
+            // check that is in progress
+            if !seen.insert(name.clone()) {
+                return false;
+            }
+        }
+
+        let mut any = |ats: Vec<&ApiType>| {
+            ats.into_iter().any(|at| self.changes_in(at, dump, seen))
+        };
+        match &at.kind {
+            ApiKind::Prim(ApiPrim::Option(at)) => any(vec![at]),
+            ApiKind::Prim(prim) => self.prim(prim, dump).is_some(),
+            ApiKind::Array(at) | ApiKind::Map(at) => any(vec![at]),
+            ApiKind::Object(obj) => {
+                any(obj.iter().map(|(_, at, _)| at).collect())
+            }
+            ApiKind::Tagged(_, vars) => {
+                any(vars.iter().map(|(_, at)| at).collect())
+            }
+            ApiKind::Combo(ats) | ApiKind::Tuple(ats) => {
+                any(ats.iter().collect())
+            }
+            // a member of an untagged union can not be told apart
+            ApiKind::Union(_)
+            | ApiKind::StrEnum(..)
+            | ApiKind::Enum(..)
+            | ApiKind::Ref(_)
+            | ApiKind::Recursive
+            | ApiKind::Unknown => false,
+        }
+    }
+
+    /// the expression that parses the json body of the response `r`.
+    /// `JSON.parse` rounds integers above 2^53, bodies with an `int64` are
+    /// parsed with `_int64` which keeps every digit of them
+    pub fn parse(&self, at: &ApiType) -> String {
+        if self.types.int64.is_converted()
+            && self.has_int64(at, &mut HashSet::new())
+        {
+            return "JSON.parse(await r.text(), _int64)".to_string();
+        }
+        "await r.json()".to_string()
+    }
+
+    fn has_int64(&self, at: &ApiType, seen: &mut HashSet<String>) -> bool {
+        if let Some(name) = &at.name {
+            if let ApiKind::Recursive = at.kind {
+                return self
+                    .named
+                    .get(name)
+                    .is_some_and(|at| self.has_int64(at, seen));
+            }
+            if !seen.insert(name.clone()) {
+                return false;
+            }
+        }
+
+        let mut any = |ats: Vec<&ApiType>| {
+            ats.into_iter().any(|at| self.has_int64(at, seen))
+        };
+        match &at.kind {
+            ApiKind::Prim(ApiPrim::Option(at)) => any(vec![at]),
+            ApiKind::Prim(prim) => matches!(prim, ApiPrim::Int64),
+            ApiKind::Array(at) | ApiKind::Map(at) => any(vec![at]),
+            ApiKind::Object(obj) => {
+                any(obj.iter().map(|(_, at, _)| at).collect())
+            }
+            ApiKind::Tagged(_, vars) => {
+                any(vars.iter().map(|(_, at)| at).collect())
+            }
+            ApiKind::Combo(ats) | ApiKind::Tuple(ats) | ApiKind::Union(ats) => {
+                any(ats.iter().collect())
+            }
+            ApiKind::StrEnum(..)
+            | ApiKind::Enum(..)
+            | ApiKind::Ref(_)
+            | ApiKind::Recursive
+            | ApiKind::Unknown => false,
+        }
+    }
+
+    /// the expression that converts `v`, `None` when nothing changes.
+    /// named types call their own function unless `top` is set
+    pub fn expr(&self, at: &ApiType, dump: bool, top: bool) -> Option<String> {
+        if let Some(name) =
+            at.name.as_ref().filter(|n| !top && self.named.contains_key(*n))
+        {
+            if !self.changes(at, dump) {
+                return None;
+            }
+            let f = if dump { "_dump" } else { "_revive" };
+            return Some(format!("{f}_{name}(v)"));
+        }
+
+        let conv = |at: &ApiType| self.expr(at, dump, false);
+        // null and missing values are kept as they are
+        let map = |src: &str, at: &ApiType| {
+            let at = match &at.kind {
+                ApiKind::Prim(ApiPrim::Option(at)) => at,
+                _ => at,
+            };
+            conv(at).map(|e| format!("_map({src}, (v: any) => {e})"))
+        };
+
+        match &at.kind {
+            ApiKind::Prim(ApiPrim::Option(at)) => {
+                conv(at).map(|e| format!("v == null ? v : {e}"))
+            }
+            ApiKind::Prim(prim) => self.prim(prim, dump).map(String::from),
+            ApiKind::Array(at) => {
+                let e = map("v", at)?;
+                Some(format!("v.map((v: any) => {e})"))
+            }
+            ApiKind::Map(at) => {
+                let e = map("v", at)?;
+                Some(format!(
+                    "Object.fromEntries(Object.entries(v).map(([k, v]: [string, any]) => [k, {e}]))"
+                ))
+            }
+            ApiKind::Object(obj) => {
+                let fields =
+                    Vec::from_iter(obj.iter().filter_map(|(name, at, _)| {
+                        let e = map(&format!("v['{name}']"), at)?;
+                        Some(format!("'{name}': {e}"))
+                    }));
+                if fields.is_empty() {
+                    return None;
+                }
+                Some(format!("({{ ...v, {} }})", fields.join(", ")))
+            }
+            ApiKind::Tagged(key, vars) => {
+                let mut out = String::new();
+                for (tag, at) in vars {
+                    if let Some(e) = conv(at) {
+                        out += &format!("v['{key}'] == '{tag}' ? {e} : ");
+                    }
+                }
+                if out.is_empty() {
+                    return None;
+                }
+                out.push('v');
+                Some(out)
+            }
+            // every part converts its own fields of the same value
+            ApiKind::Combo(ats) => ats
+                .iter()
+                .filter_map(conv)
+                .reduce(|prev, e| format!("_map({prev}, (v: any) => {e})")),
+            ApiKind::Tuple(ats) => {
+                let items = Vec::from_iter(
+                    ats.iter()
+                        .enumerate()
+                        .map(|(i, at)| map(&format!("v[{i}]"), at)),
+                );
+                if items.iter().all(Option::is_none) {
+                    return None;
+                }
+                let items = items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, e)| e.unwrap_or_else(|| format!("v[{i}]")));
+                Some(format!("[{}]", Vec::from_iter(items).join(", ")))
+            }
+            ApiKind::Union(_)
+            | ApiKind::StrEnum(..)
+            | ApiKind::Enum(..)
+            | ApiKind::Ref(_)
+            | ApiKind::Recursive
+            | ApiKind::Unknown => None,
+        }
+    }
+
+    /// the functions of named types and the helpers they use
+    pub fn ts(&self) -> String {
+        if !self.types.revives() {
+            return String::new();
+        }
+
+        let mut out = String::from(
+            "// @ts-ignore\nfunction _map(v: any, f: (v: any) => any): any { return v == null ? v : f(v) }\n",
+        );
+        if self.types.int64.is_converted() {
+            // JSON.rawJSON keeps every digit where it is supported
+            out += indoc::indoc! {"
+                // @ts-ignore
+                function _json(_: string, v: unknown): unknown {
+                    if (typeof v != 'bigint') return v
+                    // @ts-ignore
+                    return typeof JSON.rawJSON == 'function' ? JSON.rawJSON(String(v)) : Number(v)
+                }
+            "};
+        }
+        if self.types.int64.is_converted() {
+            // the source of a number is given where JSON.rawJSON is
+            out += indoc::indoc! {"
+                // @ts-ignore
+                function _int64(_: string, v: unknown, c?: { source?: string }): unknown {
+                    if (typeof v != 'number' || Number.isSafeInteger(v) || !c?.source) return v
+                    return /^-?\\d+$/.test(c.source) ? BigInt(c.source) : v
+                }
+            "};
+        }
+        for (name, at) in self.named {
+            for (dump, f, sig) in [
+                (false, "_revive", format!("(v: any): {name}")),
+                (true, "_dump", format!("(v: {name}): any")),
+            ] {
+                if let Some(e) = self
+                    .changes(at, dump)
+                    .then(|| self.expr(at, dump, true))
+                    .flatten()
+                {
+                    out += &format!(
+                        "// @ts-ignore\nfunction {f}_{name}{sig} {{ return {e} }}\n"
+                    );
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn types(toml: &str) -> Types {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn at(kind: ApiKind) -> ApiType {
+        ApiType::new(None, kind)
+    }
+
+    fn named(name: &str, kind: ApiKind) -> ApiType {
+        ApiType::new(Some(name.to_string()), kind)
+    }
+
+    fn prim(prim: ApiPrim) -> ApiType {
+        at(ApiKind::Prim(prim))
+    }
+
+    fn option(inner: ApiType) -> ApiType {
+        prim(ApiPrim::Option(Box::new(inner)))
+    }
+
+    fn object(fields: Vec<(&str, ApiType)>) -> ApiKind {
+        ApiKind::Object(Vec::from_iter(
+            fields.into_iter().map(|(k, at)| (k.to_string(), at, true)),
+        ))
+    }
+
+    #[test]
+    fn nested_arrays_and_maps() {
+        let types = types("date_time = 'date'");
+        let named = IndexMap::new();
+        let rv = Revive { types: &types, named: &named };
+
+        let list = at(ApiKind::Array(Box::new(option(at(ApiKind::Array(
+            Box::new(prim(ApiPrim::DateTime)),
+        ))))));
+        assert_eq!(
+            rv.expr(&list, false, false).unwrap(),
+            "v.map((v: any) => _map(v, (v: any) => v.map((v: any) => _map(v, (v: any) => new Date(v)))))"
+        );
+        assert_eq!(
+            rv.expr(&list, true, false).unwrap(),
+            "v.map((v: any) => _map(v, (v: any) => v.map((v: any) => _map(v, (v: any) => v.toISOString()))))"
+        );
+
+        let map = at(ApiKind::Map(Box::new(prim(ApiPrim::DateTime))));
+        assert_eq!(
+            rv.expr(&map, false, false).unwrap(),
+            "Object.fromEntries(Object.entries(v).map(([k, v]: [string, any]) => [k, _map(v, (v: any) => new Date(v))]))"
+        );
+
+        // nothing is converted when the types stay strings
+        let strings = at(ApiKind::Map(Box::new(prim(ApiPrim::Date))));
+        assert_eq!(rv.expr(&strings, false, false), None);
+    }
+
+    #[test]
+    fn tagged_unions_call_named_types() {
+        let types = types("date = 'date'");
+        let cat = named("Cat", object(vec![("born", prim(ApiPrim::Date))]));
+        let dog = named("Dog", object(vec![("name", prim(ApiPrim::Str))]));
+        let pet = named(
+            "Pet",
+            ApiKind::Tagged(
+                "kind".to_string(),
+                vec![
+                    ("cat".to_string(), cat.clone()),
+                    ("dog".to_string(), dog.clone()),
+                ],
+            ),
+        );
+        let named = IndexMap::from_iter([
+            ("Cat".to_string(), cat),
+            ("Dog".to_string(), dog.clone()),
+            ("Pet".to_string(), pet.clone()),
+        ]);
+        let rv = Revive { types: &types, named: &named };
+
+        assert_eq!(
+            rv.expr(&pet, false, true).unwrap(),
+            "v['kind'] == 'cat' ? _revive_Cat(v) : v"
+        );
+        assert_eq!(rv.expr(&pet, true, false).unwrap(), "_dump_Pet(v)");
+        assert_eq!(rv.expr(&dog, false, false), None);
+
+        let ts = rv.ts();
+        assert!(ts.contains("function _revive_Pet(v: any): Pet"));
+        assert!(!ts.contains("_revive_Dog"));
+    }
+
+    #[test]
+    fn int64_bodies_keep_their_digits() {
+        let named = IndexMap::new();
+        let body = at(object(vec![("id", prim(ApiPrim::Int64))]));
+
+        for (toml, revive, dump) in [
+            ("int64 = 'bigint'", Some("BigInt(v)"), None),
+            ("int64 = 'string'", Some("String(v)"), Some("BigInt(v)")),
+        ] {
+            let types = types(toml);
+            let rv = Revive { types: &types, named: &named };
+            assert_eq!(rv.parse(&body), "JSON.parse(await r.text(), _int64)");
+            let field = |e: &str| {
+                format!("({{ ...v, 'id': _map(v['id'], (v: any) => {e}) }})")
+            };
+            assert_eq!(rv.expr(&body, false, false), revive.map(field));
+            assert_eq!(rv.expr(&body, true, false), dump.map(field));
+            assert!(rv.ts().contains("function _int64("));
+        }
+
+        let types = types("");
+        let rv = Revive { types: &types, named: &named };
+        assert_eq!(rv.parse(&body), "await r.json()");
+        assert_eq!(rv.ts(), "");
+    }
+}
@@ -1,22 +1,15 @@
-use serde::Deserialize;
+use indexmap::IndexMap;
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
 
 use super::{
     array::Array,
     object::Object,
-    of::{AllOf, OneOf},
+    of::{AllOf, AnyOf, OneOf},
 };
 
-pub trait GetRef<'a>:
-    Fn(&Ref) -> Option<(String, &'a RefOr<OaSchema>)>
-{
-}
-impl<'a, T: Fn(&Ref) -> Option<(String, &'a RefOr<OaSchema>)>> GetRef<'a>
-    for T
-{
-}
-
 pub trait Def {
-    fn def_ts<'a, F: GetRef<'a>>(&self, get_ref: &F) -> String;
     fn is_user_defined(&self) -> bool;
 }
 
@@ -30,34 +23,34 @@ pub struct Ref {
     pub summary: String,
 }
 
+/// tells which variant of a `oneOf` or `anyOf` a value is
 #[derive(Debug, Deserialize, Clone)]
-#[serde(untagged, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Discriminator {
+    pub property_name: String,
+    /// value of the property to a ref or name of the schema
+    #[serde(default)]
+    pub mapping: IndexMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
 pub enum OaSchema {
     Array(Array),
-    Object(Object),
     OneOf(OneOf),
     AllOf(AllOf),
-    AnyOf,
+    AnyOf(AnyOf),
+    /// must be the last one, a schema without a type is an any value
+    Object(Box<Object>),
 }
 
 impl Def for OaSchema {
-    fn def_ts<'a, F: GetRef<'a>>(&self, get_ref: &F) -> String {
-        let x = match self {
-            Self::Object(o) => o.def_ts(get_ref),
-            Self::AllOf(a) => a.def_ts(get_ref),
-            Self::OneOf(a) => a.def_ts(get_ref),
-            Self::Array(a) => a.def_ts(get_ref),
-            Self::AnyOf => todo!("any of ??"),
-        };
-        format!("({x})")
-    }
     fn is_user_defined(&self) -> bool {
         match self {
             Self::Object(o) => o.is_user_defined(),
             Self::AllOf(o) => o.is_user_defined(),
             Self::OneOf(o) => o.is_user_defined(),
             Self::Array(o) => o.is_user_defined(),
-            Self::AnyOf => false,
+            Self::AnyOf(o) => o.is_user_defined(),
         }
     }
 }
@@ -70,9 +63,10 @@ pub enum SchemaType {
     AnyValue,
 }
 
+/// a schema without a `type` accepts any value
 impl Default for SchemaType {
     fn default() -> Self {
-        Self::Type(Type::default())
+        Self::AnyValue
     }
 }
 
@@ -89,24 +83,84 @@ pub enum Type {
     Null,
 }
 
-#[derive(Debug, Deserialize, Clone)]
-#[serde(untagged)]
+#[derive(Debug, Clone)]
 pub enum RefOr<T> {
     Ref(Ref),
     T(T),
 }
 
-impl<T: Def> Def for RefOr<T> {
-    fn def_ts<'a, F: GetRef<'a>>(&self, get_ref: &F) -> String {
-        let x = match self {
-            Self::T(t) => t.def_ts(get_ref),
-            Self::Ref(r) => {
-                let i = r.loc.split('/').last().unwrap();
-                i.to_string()
-            }
+/// reads `value` as `T`, the error names the path inside of `value`
+fn inner<T: DeserializeOwned, E: de::Error>(value: Value) -> Result<T, E> {
+    serde_path_to_error::deserialize(value).map_err(|e| {
+        let path = e.path().to_string();
+        match path.as_str() {
+            "." => E::custom(e.into_inner()),
+            _ => E::custom(format!("{path}: {}", e.into_inner())),
+        }
+    })
+}
+
+type Try<T, E> = fn(Value) -> Result<T, E>;
+
+/// tries the variants in order like an untagged enum would, but reports the
+/// error of the variant that `meant` picks instead of "did not match any
+/// variant"
+fn first_of<T, E: de::Error>(
+    value: Value, meant: usize, tries: &[Try<T, E>],
+) -> Result<T, E> {
+    let mut err = None;
+    for (i, f) in tries.iter().enumerate() {
+        match f(value.clone()) {
+            Ok(t) => return Ok(t),
+            Err(e) if i == meant => err = Some(e),
+            Err(_) => {}
+        }
+    }
+    Err(err.unwrap_or_else(|| E::custom("no variant matched")))
+}
+
+impl<'de> Deserialize<'de> for OaSchema {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(d)?;
+        let has = |key| value.get(key).is_some();
+        let meant = if has("items") {
+            0
+        } else if has("oneOf") {
+            1
+        } else if has("allOf") {
+            2
+        } else if has("anyOf") {
+            3
+        } else {
+            4
         };
-        format!("({x})")
+        first_of(
+            value,
+            meant,
+            &[
+                |v| inner(v).map(Self::Array),
+                |v| inner(v).map(Self::OneOf),
+                |v| inner(v).map(Self::AllOf),
+                |v| inner(v).map(Self::AnyOf),
+                |v| inner(v).map(Self::Object),
+            ],
+        )
     }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for RefOr<T> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(d)?;
+        let meant = if value.get("$ref").is_some() { 0 } else { 1 };
+        first_of(
+            value,
+            meant,
+            &[|v| inner(v).map(Self::Ref), |v| inner(v).map(Self::T)],
+        )
+    }
+}
+
+impl<T: Def> Def for RefOr<T> {
     fn is_user_defined(&self) -> bool {
         match self {
             Self::T(t) => t.is_user_defined(),
@@ -0,0 +1,264 @@
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+/// the root document of a spec and every file it references,
+/// files are loaded once when they are first referenced
+#[derive(Debug)]
+pub struct Docs {
+    docs: IndexMap<PathBuf, Value>,
+}
+
+impl Docs {
+    /// `path` of the root document, `-` when it was read from stdin
+    pub fn new(path: &Path, root: Value) -> Self {
+        Self { docs: IndexMap::from_iter([(normalize(path), root)]) }
+    }
+
+    pub fn root(&self) -> &Value {
+        &self.docs[0]
+    }
+
+    /// every file of the spec, the root document first
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.docs.keys()
+    }
+
+    /// splits a `$ref` found in the document `base` into the index of the
+    /// document it points to and a json pointer inside of it.
+    /// e.g. `./common.yaml#/components/schemas/Money`
+    pub fn locate(
+        &mut self, base: usize, r: &str,
+    ) -> std::io::Result<(usize, String)> {
+        let (file, pointer) = r.split_once('#').unwrap_or((r, ""));
+        if file.is_empty() {
+            return Ok((base, pointer.to_string()));
+        }
+
+        if file.contains("://") {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "remote refs are not supported",
+            ));
+        }
+
+        let (dir, _) = self.docs.get_index(base).expect("unknown document");
+        let path = normalize(&dir.parent().unwrap_or(Path::new("")).join(file));
+        if let Some(i) = self.docs.get_index_of(&path) {
+            return Ok((i, pointer.to_string()));
+        }
+
+        let data = std::fs::read_to_string(&path).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("{}: {e}", path.display()))
+        })?;
+        let doc = super::parse_document(&path, &data)?;
+        let (i, _) = self.docs.insert_full(path, doc);
+        Ok((i, pointer.to_string()))
+    }
+
+    /// follows schemas that are only a `$ref` to the schema they end at, so a
+    /// re-export like `Money: {$ref: './common.yaml#/components/schemas/Money'}`
+    /// is the same schema as its target. stops at refs that can't be followed
+    pub fn target(&mut self, doc: usize, pointer: &str) -> (usize, String) {
+        let mut at = (doc, pointer.to_string());
+        let mut seen = HashSet::new();
+        while seen.insert(at.clone()) {
+            let Some(r) = self
+                .get(at.0, &at.1)
+                .and_then(|v| v.get("$ref"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+            else {
+                break;
+            };
+            match self.locate(at.0, &r) {
+                Ok(next) => at = next,
+                Err(_) => break,
+            }
+        }
+        at
+    }
+
+    pub fn get(&self, doc: usize, pointer: &str) -> Option<&Value> {
+        self.docs[doc].pointer(pointer)
+    }
+
+    /// location used in diagnostics, also unique for every schema.
+    /// e.g. `#/components/schemas/User` or `common.yaml#/components/...`
+    pub fn loc(&self, doc: usize, pointer: &str) -> String {
+        if doc == 0 {
+            return format!("#{pointer}");
+        }
+        let (path, _) = self.docs.get_index(doc).expect("unknown document");
+        format!("{}#{pointer}", path.display())
+    }
+
+    /// name of the type a ref points to, `/components/schemas/{name}`
+    /// or the file name when the ref points to a whole document
+    pub fn ref_name(&self, doc: usize, pointer: &str) -> Option<String> {
+        if pointer.is_empty() {
+            let (path, _) = self.docs.get_index(doc)?;
+            let stem = path.file_stem()?.to_string_lossy();
+            return Some(stem.to_string());
+        }
+
+        let name = pointer.strip_prefix("/components/schemas/")?;
+        if name.contains('/') {
+            return None;
+        }
+        Some(name.replace("~1", "/").replace("~0", "~"))
+    }
+}
+
+/// removes `.` and resolves `..` without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    out.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                out.pop();
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// a directory that is removed when it is dropped
+    pub(crate) struct TempDir(pub PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// writes `files` into a fresh directory and opens the first as the root,
+    /// the files are there until the [`TempDir`] is dropped
+    pub(crate) fn docs(name: &str, files: &[(&str, &str)]) -> (TempDir, Docs) {
+        let dir = std::env::temp_dir()
+            .join(format!("argon-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        for (path, data) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, data).unwrap();
+        }
+        let root = dir.join(files[0].0);
+        let doc = crate::openapi::parse_document(
+            &root,
+            &std::fs::read_to_string(&root).unwrap(),
+        )
+        .unwrap();
+        (TempDir(dir), Docs::new(&root, doc))
+    }
+
+    #[test]
+    fn normalize_paths() {
+        let n = |p: &str| normalize(Path::new(p));
+        assert_eq!(n("a/./b/../c.yaml"), PathBuf::from("a/c.yaml"));
+        assert_eq!(n("./a.yaml"), PathBuf::from("a.yaml"));
+        assert_eq!(n("a/../../b.yaml"), PathBuf::from("../b.yaml"));
+        assert_eq!(n("../../b.yaml"), PathBuf::from("../../b.yaml"));
+        assert_eq!(n("/a/../b.yaml"), PathBuf::from("/b.yaml"));
+    }
+
+    #[test]
+    fn locate_refs() {
+        let (dir, mut docs) = docs(
+            "locate",
+            &[
+                ("spec/openapi.yaml", "openapi: 3.1.0"),
+                ("spec/common.yaml", "a: 1"),
+                ("shared/x.yaml", "b: 2"),
+            ],
+        );
+
+        assert_eq!(docs.locate(0, "#/a/b").unwrap(), (0, "/a/b".into()));
+
+        let common = docs.locate(0, "./common.yaml#/a").unwrap();
+        assert_eq!(common, (1, "/a".into()));
+        assert_eq!(docs.get(1, "/a"), Some(&Value::from(1)));
+        // another spelling of the same file is not loaded again
+        let again = docs.locate(0, "../shared/../spec/common.yaml#/a");
+        assert_eq!(again.unwrap(), (1, "/a".into()));
+
+        let x = docs.locate(1, "../shared/x.yaml#/b").unwrap();
+        assert_eq!(x, (2, "/b".into()));
+        assert_eq!(
+            docs.loc(2, "/b"),
+            format!("{}#/b", dir.0.join("shared/x.yaml").display())
+        );
+        // a ref inside of another file resolves from that file
+        assert_eq!(docs.locate(2, "#/b").unwrap(), (2, "/b".into()));
+
+        assert!(docs.locate(0, "missing.yaml#/a").is_err());
+        assert!(docs.locate(0, "https://example.com/a.yaml#/a").is_err());
+        assert_eq!(docs.paths().count(), 3);
+    }
+
+    #[test]
+    fn ref_names() {
+        let (_dir, mut docs) = docs(
+            "names",
+            &[
+                ("openapi.yaml", "openapi: 3.1.0"),
+                ("money.yaml", "type: string"),
+            ],
+        );
+        let name = |docs: &Docs, p: &str| docs.ref_name(0, p);
+
+        assert_eq!(
+            name(&docs, "/components/schemas/User"),
+            Some("User".into())
+        );
+        assert_eq!(
+            name(&docs, "/components/schemas/a~1b~0c"),
+            Some("a/b~c".into())
+        );
+        assert_eq!(name(&docs, "/components/schemas/User/properties/id"), None);
+        assert_eq!(name(&docs, "/paths/~1a"), None);
+
+        let (doc, pointer) = docs.locate(0, "./money.yaml").unwrap();
+        assert_eq!(docs.ref_name(doc, &pointer), Some("money".into()));
+    }
+
+    #[test]
+    fn target_follows_reexports() {
+        let (_dir, mut docs) = docs(
+            "target",
+            &[
+                (
+                    "spec/openapi.yaml",
+                    "components: {schemas: {\
+                    Money: {$ref: './common.yaml#/components/schemas/Money'},\
+                    Loop: {$ref: '#/components/schemas/Loop'}}}",
+                ),
+                (
+                    "spec/common.yaml",
+                    "components: {schemas: {\
+                    Money: {$ref: '../shared/money.yaml'}}}",
+                ),
+                ("shared/money.yaml", "type: string"),
+            ],
+        );
+
+        let (doc, pointer) = docs.target(0, "/components/schemas/Money");
+        assert_eq!((doc, pointer.as_str()), (2, ""));
+        let (doc, pointer) = docs.target(0, "/components/schemas/Loop");
+        assert_eq!((doc, pointer.as_str()), (0, "/components/schemas/Loop"));
+        let (doc, pointer) = docs.target(0, "/components/schemas/Other");
+        assert_eq!((doc, pointer.as_str()), (0, "/components/schemas/Other"));
+    }
+}
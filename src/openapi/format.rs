@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "lowercase", untagged)]
@@ -10,7 +10,20 @@ pub enum SchemaFormat {
     Custom(String),
 }
 
-#[derive(Debug, Deserialize, Clone, Copy)]
+impl SchemaFormat {
+    /// the format as it is written in the spec
+    pub fn name(&self) -> String {
+        match self {
+            Self::KnownFormat(kf) => match serde_json::to_value(kf) {
+                Ok(serde_json::Value::String(name)) => name,
+                _ => unreachable!("known formats are strings"),
+            },
+            Self::Custom(cf) => cf.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
 #[serde(rename_all = "kebab-case")]
 pub enum KnownFormat {
     /// 8 bit integer.
@@ -2,90 +2,159 @@
 
 pub mod array;
 pub mod common;
+pub mod docs;
 pub mod format;
 pub mod object;
 pub mod of;
 pub mod path;
 
 use indexmap::IndexMap;
-use indoc::formatdoc;
-use serde::Deserialize;
-use std::{collections::HashSet, io::Write};
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+use std::path::Path;
 
-use self::common::{Def, Ref, RefOr};
+use crate::diag::{ptr, Diags, Severity};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 pub struct OpenApi {
     pub paths: IndexMap<String, path::PathItem>,
     pub components: OaComponents,
     pub info: OaInfo,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Default)]
 pub struct OaInfo {
+    #[serde(deserialize_with = "scalar_string")]
     pub version: String,
 }
 
-#[derive(Debug, Deserialize)]
+/// yaml reads `version: 1.0` as a number
+fn scalar_string<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    match Value::deserialize(d)? {
+        Value::String(s) => Ok(s),
+        Value::Number(n) => Ok(n.to_string()),
+        v => Err(D::Error::custom(format!("expected a string, found {v}"))),
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct OaComponents {
+    #[serde(default)]
     pub schemas: IndexMap<String, common::RefOr<common::OaSchema>>,
 }
 
-pub fn generate(oa: &OpenApi) -> std::io::Result<()> {
-    let mut ts = std::fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open("argon-data/gen.ts")?;
-
-    let get_ref = |loc: &Ref| {
-        let i = loc.loc.split('/').last().unwrap();
-        match oa.components.schemas.get(i) {
-            Some(v) => Some((i.to_string(), v)),
-            None => None,
-        }
+/// parses a json or yaml document. yaml is detected by the file extension
+/// or, when reading from stdin, by the document not starting with `{`
+pub fn parse_document(
+    path: &Path, data: &str,
+) -> std::io::Result<serde_json::Value> {
+    let invalid =
+        |e: String| std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+
+    let yaml = match path.extension().and_then(|e| e.to_str()) {
+        Some("yaml" | "yml") => true,
+        Some("json") => false,
+        _ => !data.trim_start().starts_with('{'),
     };
 
-    ts.write_all(
-        formatdoc! {"
-            // DO NOT EDIT THIS FILE.
-            // THIS IS A GENERATED FILE AND ANY EDIT WILL BE REPLACED
-            import * as ud from './user_defined';
-            export const API_VERSION = '{}';
-            
-            
-        ", oa.info.version}
-        .as_bytes(),
-    )?;
-
-    for (ident, s) in oa.components.schemas.iter() {
-        let RefOr::T(s) = s else { continue };
-
-        let def = s.def_ts(&get_ref);
-        if s.is_user_defined() {
-            continue;
-        }
-        ts.write_all(b"// DO NOT EDIT THIS FILE\n")?;
-        ts.write_all(format!("export type {ident} = {def};\n").as_bytes())?;
+    if yaml {
+        serde_yaml::from_str(data).map_err(|e| invalid(e.to_string()))
+    } else {
+        serde_json::from_str(data).map_err(|e| invalid(e.to_string()))
     }
+}
+
+const METHODS: [&str; 8] =
+    ["get", "put", "post", "patch", "delete", "options", "head", "trace"];
 
-    let mut names = HashSet::<String>::with_capacity(oa.paths.len());
-    let has_name = |name: &str| name.contains(name);
-
-    for (url, p) in oa.paths.iter() {
-        macro_rules! dop {
-            ($($name:ident),*) => {
-                $(if let Some(op) = &p.$name {
-                    let (def, name) = op.def_ts(url, stringify!($name), &get_ref, &has_name);
-                    names.insert(name);
-                    ts.write_all(b"// DO NOT EDIT THIS FILE\n")?;
-                    ts.write_all(def.as_bytes())?;
-                })*
-            };
+impl OpenApi {
+    /// reads the spec one operation and schema at a time. the ones that do
+    /// not match the openapi structure are reported at their pointer and
+    /// left out, in lenient mode only as a warning
+    pub fn from_document(
+        doc: &Value, lenient: bool, diags: &mut Diags,
+    ) -> Self {
+        let severity =
+            if lenient { Severity::Warning } else { Severity::Error };
+        let empty = Value::Object(Map::new());
+
+        let info = doc.get("info").unwrap_or(&Value::Null);
+        let info = read(info, "#/info", Severity::Error, diags);
+
+        let mut paths = IndexMap::new();
+        match doc.get("paths").unwrap_or(&empty) {
+            Value::Object(items) => {
+                for (url, item) in items {
+                    let loc = ptr("#/paths", url);
+                    let mut item = item.clone();
+                    // a broken operation does not take the others with it
+                    if let Value::Object(map) = &mut item {
+                        for method in METHODS {
+                            let Some(op) = map.get(method) else { continue };
+                            let oloc = ptr(&loc, method);
+                            if read::<path::Operation>(
+                                op, &oloc, severity, diags,
+                            )
+                            .is_none()
+                            {
+                                map.remove(method);
+                            }
+                        }
+                    }
+                    if let Some(pi) = read(&item, &loc, severity, diags) {
+                        paths.insert(url.clone(), pi);
+                    }
+                }
+            }
+            _ => diags.error("#/paths", "paths must be an object"),
+        }
+
+        let mut components = OaComponents::default();
+        let schemas = doc
+            .get("components")
+            .and_then(|c| c.get("schemas"))
+            .unwrap_or(&empty);
+        match schemas {
+            Value::Object(items) => {
+                for (name, schema) in items {
+                    let loc = ptr("#/components/schemas", name);
+                    if let Some(s) = read(schema, &loc, severity, diags) {
+                        components.schemas.insert(name.clone(), s);
+                    }
+                }
+            }
+            _ => {
+                diags.error("#/components/schemas", "schemas must be an object")
+            }
         }
 
-        dop!(get, put, post, delete, patch);
+        Self { paths, components, info: info.unwrap_or_default() }
     }
+}
+
+/// deserializes `value`, a failure is reported at the pointer of the
+/// part that did not match, starting from `loc`
+fn read<T: DeserializeOwned>(
+    value: &Value, loc: &str, severity: Severity, diags: &mut Diags,
+) -> Option<T> {
+    use serde_path_to_error::Segment;
 
-    Ok(())
+    match serde_path_to_error::deserialize(value) {
+        Ok(t) => Some(t),
+        Err(e) => {
+            let mut at = loc.to_string();
+            for seg in e.path().iter() {
+                at = match seg {
+                    Segment::Seq { index } => ptr(&at, index),
+                    Segment::Map { key } => ptr(&at, key),
+                    Segment::Enum { variant } => ptr(&at, variant),
+                    Segment::Unknown => break,
+                };
+            }
+            diags.push(severity, &at, e.into_inner());
+            None
+        }
+    }
 }
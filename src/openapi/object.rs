@@ -3,10 +3,7 @@
 use indexmap::IndexMap;
 use serde::Deserialize;
 
-use super::{
-    common::*,
-    format::{KnownFormat, SchemaFormat},
-};
+use super::{common::*, format::SchemaFormat};
 
 #[derive(Debug, Deserialize, Clone)]
 #[serde(untagged)]
@@ -20,7 +17,7 @@ pub enum Number {
 }
 
 #[derive(Debug, Deserialize, Clone)]
-#[serde(untagged)]
+#[serde(untagged, bound = "T: serde::de::DeserializeOwned")]
 pub enum AdditionalProperties<T> {
     /// Use when value type of the map is a known [`Schema`] or [`Ref`] to the [`Schema`].
     RefOr(RefOr<T>),
@@ -31,13 +28,21 @@ pub enum AdditionalProperties<T> {
 #[derive(Debug, Deserialize, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Object {
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default)]
     pub schema_type: SchemaType,
     pub title: Option<String>,
     pub format: Option<SchemaFormat>,
     pub description: Option<String>,
     #[serde(rename = "enum")]
     pub enum_values: Option<Vec<serde_json::Value>>,
+    #[serde(rename = "const")]
+    pub const_value: Option<serde_json::Value>,
+    /// names of the `enum` members
+    #[serde(rename = "x-enum-varnames")]
+    pub enum_varnames: Option<Vec<String>>,
+    /// docs of the `enum` members
+    #[serde(rename = "x-enum-descriptions")]
+    pub enum_descriptions: Option<Vec<String>>,
     #[serde(default)]
     pub required: Vec<String>,
     #[serde(default)]
@@ -125,76 +130,6 @@ pub struct Object {
 }
 
 impl Def for Object {
-    fn def_ts<'a, F: GetRef<'a>>(&self, get_ref: &F) -> String {
-        let ty = match &self.schema_type {
-            SchemaType::AnyValue => return "any".to_string(),
-            SchemaType::Array(v) => {
-                if v[0] == Type::String && v[1] == Type::Null {
-                    return "string | null".to_string();
-                }
-                todo!("type array: {v:?}");
-            }
-            SchemaType::Type(t) => t,
-        };
-
-        // if let Some(ev) = &self.enum_values {
-        //     println!("{:?} / ev: {ev:#?}", self.schema_type);
-        // }
-
-        match ty {
-            Type::String => {
-                if let Some(ev) = &self.enum_values {
-                    let mut uni = Vec::<String>::with_capacity(ev.len());
-                    for v in ev {
-                        uni.push(format!(r##""{}""##, v.as_str().unwrap()));
-                    }
-                    return uni.join("|");
-                }
-
-                if let Some(SchemaFormat::KnownFormat(KnownFormat::Binary)) =
-                    &self.format
-                {
-                    return "File".to_string();
-                }
-
-                "string".to_string()
-            }
-            Type::Object => {
-                let mut o = String::from("{\n");
-                for (k, v) in self.properties.iter() {
-                    let xv = match v {
-                        RefOr::T(t) => t.def_ts(get_ref),
-                        RefOr::Ref(r) => match get_ref(r) {
-                            Some((i, _)) => i,
-                            None => "any".to_string(),
-                        },
-                    };
-                    o.push_str("    ");
-                    o.push_str(k);
-                    o.push_str(": ");
-                    o.push_str(&xv);
-                    // if xv == "Gene" {
-                    //     o.push_str(" | null");
-                    // }
-                    o.push_str(",\n");
-                }
-                o.push_str("}\n");
-                o
-            }
-            Type::Integer | Type::Number => {
-                // println!("int: {self:#?}");
-                "number".to_string()
-            }
-            Type::Boolean => "boolean".to_string(),
-            Type::Null => "null".to_string(),
-            Type::Array => {
-                "[]".to_string()
-                // println!("{self:#?}");
-                // todo!("hi")
-            }
-        }
-    }
-
     fn is_user_defined(&self) -> bool {
         if let Some(desc) = &self.description {
             if desc.contains("#user_defined") {
@@ -5,6 +5,7 @@ use serde::Deserialize;
 pub struct OneOf {
     #[serde(rename = "oneOf")]
     pub items: Vec<RefOr<OaSchema>>,
+    pub discriminator: Option<Discriminator>,
     #[serde(rename = "type", default)]
     pub schema_type: SchemaType,
     pub title: Option<String>,
@@ -12,15 +13,35 @@ pub struct OneOf {
 }
 
 impl Def for OneOf {
-    fn def_ts<'a, F: GetRef<'a>>(&self, get_ref: &F) -> String {
-        let mut uni = Vec::<String>::with_capacity(self.items.len());
-        for i in self.items.iter() {
-            uni.push(i.def_ts(get_ref));
+    fn is_user_defined(&self) -> bool {
+        if let Some(s) = &self.description {
+            if s.contains("#user_defined") {
+                return true;
+            }
         }
 
-        uni.join("|")
+        if let Some(s) = &self.title {
+            if s.contains("#user_defined") {
+                return true;
+            }
+        }
+
+        false
     }
+}
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct AnyOf {
+    #[serde(rename = "anyOf")]
+    pub items: Vec<RefOr<OaSchema>>,
+    pub discriminator: Option<Discriminator>,
+    #[serde(rename = "type", default)]
+    pub schema_type: SchemaType,
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+impl Def for AnyOf {
     fn is_user_defined(&self) -> bool {
         if let Some(s) = &self.description {
             if s.contains("#user_defined") {
@@ -38,15 +59,6 @@ impl Def for OneOf {
     }
 }
 
-// #[derive(Debug, Deserialize)]
-// pub struct AnyOf {
-//     #[serde(rename = "anyOf")]
-//     pub items: Vec<RefOr<OaSchema>>,
-//     #[serde(rename = "type", default)]
-//     pub schema_type: SchemaType,
-//     pub description: Option<String>,
-// }
-
 #[derive(Debug, Deserialize, Clone)]
 pub struct AllOf {
     #[serde(rename = "allOf")]
@@ -58,15 +70,6 @@ pub struct AllOf {
 }
 
 impl Def for AllOf {
-    fn def_ts<'a, F: GetRef<'a>>(&self, get_ref: &F) -> String {
-        let mut and = Vec::<String>::with_capacity(self.items.len());
-        for i in self.items.iter() {
-            and.push(i.def_ts(get_ref));
-        }
-
-        and.join("&")
-    }
-
     fn is_user_defined(&self) -> bool {
         if let Some(s) = &self.description {
             if s.contains("#user_defined") {
@@ -1,9 +1,7 @@
-use super::common::{GetRef, OaSchema, RefOr};
+use super::common::{OaSchema, RefOr};
 use crate::openapi::common::Def;
-use core::panic;
-use indoc::formatdoc;
-use serde::Deserialize;
 use indexmap::IndexMap;
+use serde::Deserialize;
 
 #[derive(Debug, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -11,30 +9,36 @@ pub struct PathItem {
     // pub summary: Option<String>,
     // pub description: Option<String>,
     // pub servers: Option<Vec<Server>>,
-    // pub parameters: Option<Vec<Parameter>>,
+    /// shared by every operation of the path, operations can override them
+    pub parameters: Option<Vec<RefOr<Parameter>>>,
     pub get: Option<Operation>,
     pub put: Option<Operation>,
     pub post: Option<Operation>,
     pub patch: Option<Operation>,
     pub delete: Option<Operation>,
-    // pub options: Option<Operation>,
-    // pub head: Option<Operation>,
-    // pub trace: Option<Operation>,
+    pub options: Option<Operation>,
+    pub head: Option<Operation>,
+    pub trace: Option<Operation>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Parameter {
     pub name: String,
     #[serde(rename = "in")]
     pub parameter_in: ParameterIn,
     pub description: Option<String>,
+    #[serde(default)]
     pub required: bool,
     pub deprecated: Option<bool>,
     pub schema: Option<RefOr<OaSchema>>,
+    pub style: Option<ParameterStyle>,
+    pub explode: Option<bool>,
+    #[serde(default)]
+    pub allow_reserved: bool,
 }
 
-#[derive(Debug, Deserialize, Default, Clone, Copy)]
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ParameterIn {
     Query,
@@ -44,239 +48,72 @@ pub enum ParameterIn {
     Cookie,
 }
 
-#[derive(Debug, Deserialize, Default)]
+/// how a parameter is serialized
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
-pub struct Operation {
-    pub tags: Option<Vec<String>>,
-    pub summary: Option<String>,
-    pub description: Option<String>,
-    pub operation_id: Option<String>,
-    pub parameters: Option<Vec<Parameter>>,
-    pub request_body: Option<RequestBody>,
-    pub responses: IndexMap<String, RefOr<Response>>,
-    pub deprecated: Option<bool>,
-    // pub security: Option<Vec<SecurityRequirement>>,
+pub enum ParameterStyle {
+    Matrix,
+    Label,
+    Form,
+    Simple,
+    SpaceDelimited,
+    PipeDelimited,
+    DeepObject,
 }
 
-impl Operation {
-    pub fn def_ts<'a, F: GetRef<'a>>(
-        &self, url: &str, method: &str, get_ref: &F,
-        _has_name: &impl Fn(&str) -> bool,
-    ) -> (String, String) {
-        let name = self.url_to_name(url, method, self.is_list(get_ref));
-        macro_rules! deopt {
-            ($name:ident) => {
-                match &self.$name {
-                    Some(v) => v.as_str(),
-                    None => "",
-                }
-            };
+impl ParameterStyle {
+    pub fn default_for(pin: ParameterIn) -> Self {
+        match pin {
+            ParameterIn::Query | ParameterIn::Cookie => Self::Form,
+            ParameterIn::Path | ParameterIn::Header => Self::Simple,
         }
-
-        let sum = deopt!(summary);
-        let des = deopt!(description);
-        let mut input = Vec::<String>::with_capacity(2);
-        let mut params_names = Vec::<&str>::new();
-        let mut query_params = Vec::<&str>::new();
-
-        if let Some(ps) = &self.parameters {
-            let mut def = String::from("{");
-            params_names.reserve_exact(ps.len());
-            for p in ps {
-                def.push_str(&p.name);
-                params_names.push(&p.name);
-                if matches!(p.parameter_in, ParameterIn::Query) {
-                    query_params.push(&p.name);
-                }
-                if !p.required {
-                    assert!(false);
-                    def.push('?');
-                }
-                def.push(':');
-                let Some(s) = &p.schema else {
-                    def.push_str("any,");
-                    continue;
-                };
-
-                let ty = match s {
-                    RefOr::T(t) => t.def_ts(get_ref),
-                    RefOr::Ref(r) => match get_ref(&r) {
-                        Some((i, _)) => i,
-                        None => "any".to_string(),
-                    },
-                };
-                def.push_str(&ty);
-                def.push(',');
-            }
-            def.push('}');
-            if !ps.is_empty() {
-                input.push(format!("params: {def}"))
-            }
-        }
-
-        if let Some(rq) = &self.request_body {
-            let mut body = String::from("body");
-            if !rq.required.unwrap_or(false) {
-                body.push('?');
-            }
-            body.push(':');
-            body.push_str(&rq.def_ts(get_ref));
-            input.push(body);
-        }
-
-        input.push("override: Partial<ud.HttpxProps> = {}".to_string());
-        let input = input.join(",");
-        let ts_url = url.replace('{', "${");
-        let mut unwrap_params = String::new();
-        if !params_names.is_empty() {
-            unwrap_params +=
-                &format!("let {{ {} }} = params;", params_names.join(","));
-        }
-
-        let query_params = query_params.join(",");
-
-        let method_upper = method.to_uppercase();
-        let (outy, http_out_type) = self.output_type(get_ref);
-        let (body, content_type) = self.body(get_ref);
-        let headers = if content_type == "multipart/form-data" {
-            "{}".to_string()
-        } else {
-            format!("{{ 'Content-Type': '{content_type}' }}")
-        };
-
-        let def = formatdoc! {r#"
-            /**
-            {sum}
-            {des}
-            */
-            export async function {name} ({input}) : Promise<ud.Result<{outy}>> {{
-                {unwrap_params}
-                {body}
-                return new Promise((resolve, reject) => {{
-                    ud.httpx({{
-                        url: `{ts_url}`,
-                        method: "{method_upper}",
-                        params: {{ {query_params} }},
-                        type: "{http_out_type}",
-                        headers: {headers},
-                        data,
-                        reject,
-                        onLoad(x) {{
-                            resolve({{
-                                x,
-                                status: x.status,
-                                body: x.response,
-                                ok(): this is ud.Ok<{outy}> {{
-                                    return this.status == 200
-                                }},
-                                err(): this is ud.Err {{
-                                    return !this.ok()
-                                }},
-                            }})
-                        }},
-                        ...override
-                    }})
-                }})
-            }}
-        "#,
-        };
-        (def, name)
     }
 
-    fn body<'a, F: GetRef<'a>>(&self, get_ref: &F) -> (String, &str) {
-        let Some(rq) = &self.request_body else {
-            return ("let data = undefined;".to_string(), "");
-        };
-        let (ct, cc) = rq.content.iter().next().unwrap();
-        match ct.as_str() {
-            "multipart/form-data" => {
-                let Some(cs) = &cc.schema else { panic!("no schema") };
-                let mut out = String::from("let data = new FormData;\n");
-                let cs = match cs {
-                    RefOr::T(t) => t,
-                    RefOr::Ref(r) => {
-                        let RefOr::T(t) = get_ref(r).unwrap().1 else {
-                            panic!("nested ref")
-                        };
-                        t
-                    }
-                };
-
-                let OaSchema::Object(cs) = cs else { panic!("must be object") };
-                for (k, p) in cs.properties.iter() {
-                    out.push_str(r#"data.set('"#);
-                    out.push_str(k);
-                    out.push_str("', ");
-                    match p {
-                        RefOr::T(_) => {
-                            out.push_str("body.");
-                            out.push_str(k);
-                            // out.push_str(" + ''");
-                        }
-                        RefOr::Ref(_) => {
-                            out.push_str("new Blob([JSON.stringify(");
-                            out.push_str("body.");
-                            out.push_str(k);
-                            out.push_str(")], { type: 'application/json' })");
-                        }
-                    }
-                    out.push_str(");\n");
-                }
-
-                // out.push_str(&format!("/*{cs:#?}*/"));
-
-                (out, "multipart/form-data")
+    pub fn allowed_in(&self, pin: ParameterIn) -> bool {
+        match pin {
+            ParameterIn::Path => {
+                matches!(self, Self::Matrix | Self::Label | Self::Simple)
             }
-            "application/json" => (
-                "let data = JSON.stringify(body);".to_string(),
-                "application/json",
+            ParameterIn::Query => matches!(
+                self,
+                Self::Form
+                    | Self::SpaceDelimited
+                    | Self::PipeDelimited
+                    | Self::DeepObject
             ),
-            "text/plain" => ("let data = body;".to_string(), "text/plain"),
-            _ => panic!("unknown request body: {ct}"),
+            ParameterIn::Header => matches!(self, Self::Simple),
+            ParameterIn::Cookie => matches!(self, Self::Form),
         }
     }
 
-    fn output_type<'a, F: GetRef<'a>>(&self, get_ref: &F) -> (String, String) {
-        let json_ty = String::from("json");
-        let Some(RefOr::T(res)) = self.responses.get("200") else {
-            return ("any".to_string(), json_ty);
-        };
-        if res.content.is_empty() {
-            return ("void".to_string(), json_ty);
-        }
-        assert_eq!(res.content.len(), 1);
-        let (ct, cc) = res.content.iter().next().unwrap();
-        if ct == "text/plain" {
-            return ("string".to_string(), "text".to_string());
-        }
-        if ct == "application/octet-stream" {
-            return ("ArrayBuffer".to_string(), "arraybuffer".to_string());
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Matrix => "matrix",
+            Self::Label => "label",
+            Self::Form => "form",
+            Self::Simple => "simple",
+            Self::SpaceDelimited => "spaceDelimited",
+            Self::PipeDelimited => "pipeDelimited",
+            Self::DeepObject => "deepObject",
         }
-        assert_eq!(ct, "application/json");
-        let Some(cc) = &cc.schema else { return ("any".to_string(), json_ty) };
-
-        (cc.def_ts(get_ref), json_ty)
     }
+}
 
-    fn is_list<'a, F: GetRef<'a>>(&self, get_ref: &F) -> bool {
-        let Some(res) = self.responses.get("200") else { return false };
-        let RefOr::T(res) = res else { panic!("response is a ref") };
-        let Some(c) = res.content.get("application/json") else { return false };
-        let Some(c) = &c.schema else { return false };
-        let c = match &c {
-            RefOr::T(t) => t,
-            RefOr::Ref(r) => {
-                let Some((_, x)) = get_ref(r) else { return false };
-                let RefOr::T(x) = x else { return false };
-                x
-            }
-        };
-
-        let OaSchema::Array(_) = c else { return false };
-
-        true
-    }
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    pub tags: Option<Vec<String>>,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub operation_id: Option<String>,
+    pub parameters: Option<Vec<RefOr<Parameter>>>,
+    pub request_body: Option<RefOr<RequestBody>>,
+    pub responses: IndexMap<String, RefOr<Response>>,
+    pub deprecated: Option<bool>,
+    // pub security: Option<Vec<SecurityRequirement>>,
+}
 
+impl Operation {
     pub fn url_to_name(
         &self, url: &str, method: &str, is_list: bool,
     ) -> String {
@@ -326,31 +163,34 @@ impl Operation {
     }
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Response {
     pub description: String,
     /// Map of headers identified by their name. `Content-Type` header will be ignored.
     #[serde(default)]
-    pub headers: IndexMap<String, Header>,
+    pub headers: IndexMap<String, RefOr<Header>>,
     #[serde(default)]
     pub content: IndexMap<String, Content>,
     // #[serde(default)]
     // pub links: BTreeMap<String, RefOr<Link>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct Header {
-    pub schema: RefOr<OaSchema>,
+    pub schema: Option<RefOr<OaSchema>>,
+    /// a header is described by either `schema` or `content`
+    #[serde(default)]
+    pub content: IndexMap<String, Content>,
     pub description: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, Clone)]
 pub struct Content {
     pub schema: Option<RefOr<OaSchema>>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RequestBody {
     pub description: Option<String>,
@@ -359,29 +199,6 @@ pub struct RequestBody {
 }
 
 impl Def for RequestBody {
-    fn def_ts<'a, F: GetRef<'a>>(&self, get_ref: &F) -> String {
-        assert!(self.required.is_some());
-        assert_eq!(self.content.len(), 1);
-        let (ct, c) = self.content.iter().next().unwrap();
-        let Some(cs) = &c.schema else {
-            return "any".to_string();
-        };
-        match ct.as_str() {
-            "application/json" | "multipart/form-data" => {
-                match cs {
-                    RefOr::Ref(r) => match get_ref(r) {
-                        Some((i, _)) => i,
-                        None => "any".to_string(),
-                    },
-                    RefOr::T(t) => t.def_ts(get_ref),
-                }
-                // println!("{c:#?}");
-            }
-            "text/plain" => "string".to_string(),
-            _ => panic!("unknown request body content: {ct}"),
-        }
-    }
-
     fn is_user_defined(&self) -> bool {
         if let Some(s) = &self.description {
             if s.contains("#user_defined") {
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
+
+use crate::cli::Cli;
+use crate::config::is_std;
+
+type Stamps = Vec<(PathBuf, Option<SystemTime>)>;
+
+/// polls the config and spec files and regenerates when any of them changes
+pub fn watch(cli: &Cli, interval: Duration) -> ExitCode {
+    if cli.check {
+        eprintln!("error: --check can not be used with watch");
+        return ExitCode::FAILURE;
+    }
+
+    let mut last = Stamps::new();
+    let mut sources = Vec::new();
+    loop {
+        let now = match stamps(cli, &sources) {
+            Ok(now) => now,
+            Err(e) => {
+                eprintln!("error: {e}");
+                return ExitCode::FAILURE;
+            }
+        };
+
+        if now != last {
+            last = now;
+            match cli.config() {
+                Ok(config) => {
+                    sources.clear();
+                    crate::generate(&config, false, &mut sources);
+                    // pick up the files that are referenced now
+                    last = stamps(cli, &sources).unwrap_or(last);
+                }
+                Err(e) => eprintln!("error: {e}"),
+            }
+            eprintln!("watching for changes ...");
+        }
+
+        std::thread::sleep(interval);
+    }
+}
+
+/// modification time of the config, every spec input and the files
+/// referenced by them
+fn stamps(cli: &Cli, sources: &[PathBuf]) -> std::io::Result<Stamps> {
+    let mut files = Vec::from_iter(cli.config_path()?);
+    files.extend_from_slice(sources);
+    // a broken config is reported when generating
+    if let Ok(config) = cli.config() {
+        for spec in config.specs {
+            if is_std(&spec.input) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "stdin can not be watched",
+                ));
+            }
+            files.push(spec.input);
+        }
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|f| {
+            let modified = std::fs::metadata(&f).and_then(|m| m.modified());
+            (f, modified.ok())
+        })
+        .collect())
+}